@@ -0,0 +1,43 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use milhouse::List;
+
+type C = typenum::U1099511627776;
+const N: u64 = 1_000_000;
+const NUM_UPDATES: u64 = 100_000;
+
+pub fn apply_updates_list_u64(c: &mut Criterion) {
+    let size = N;
+
+    let mut list = List::<u64, C>::try_from_iter(0..size).unwrap();
+    for i in 0..NUM_UPDATES {
+        // Scatter the updates across the whole list.
+        let index = (i * 7919) % size;
+        *list.get_mut(index as usize).unwrap() += 1;
+    }
+
+    c.bench_with_input(
+        BenchmarkId::new("apply_updates_sequential", size),
+        &list,
+        |b, list| {
+            b.iter_batched(
+                || list.clone(),
+                |mut list| list.apply_updates().unwrap(),
+                BatchSize::LargeInput,
+            );
+        },
+    );
+    c.bench_with_input(
+        BenchmarkId::new("apply_updates_parallel", size),
+        &list,
+        |b, list| {
+            b.iter_batched(
+                || list.clone(),
+                |mut list| list.apply_updates_par().unwrap(),
+                BatchSize::LargeInput,
+            );
+        },
+    );
+}
+
+criterion_group!(benches, apply_updates_list_u64);
+criterion_main!(benches);
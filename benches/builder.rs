@@ -0,0 +1,38 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use milhouse::List;
+
+type C = typenum::U1099511627776;
+const N: u64 = 1_000_000;
+
+pub fn builder_u64(c: &mut Criterion) {
+    let size = N;
+    let values = (0..size).collect::<Vec<u64>>();
+
+    c.bench_with_input(
+        BenchmarkId::new("builder_push", size),
+        &values,
+        |b, values| {
+            b.iter(|| {
+                let mut builder = List::<u64, C>::builder().unwrap();
+                for value in values {
+                    builder.push(*value).unwrap();
+                }
+                builder.finish().unwrap()
+            });
+        },
+    );
+    c.bench_with_input(
+        BenchmarkId::new("builder_push_many", size),
+        &values,
+        |b, values| {
+            b.iter(|| {
+                let mut builder = List::<u64, C>::builder().unwrap();
+                builder.push_many(values).unwrap();
+                builder.finish().unwrap()
+            });
+        },
+    );
+}
+
+criterion_group!(benches, builder_u64);
+criterion_main!(benches);
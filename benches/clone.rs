@@ -0,0 +1,59 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use milhouse::List;
+use ssz::{Decode, Encode};
+use tree_hash::{Hash256, TreeHash};
+
+type C = typenum::U1099511627776;
+const N: u64 = 800_000;
+
+pub fn clone_list(c: &mut Criterion) {
+    let size = N;
+
+    let list_u64 = List::<u64, C>::try_from_iter(0..size).unwrap();
+    list_u64.tree_hash_root();
+    let list_hash256 =
+        List::<Hash256, C>::try_from_iter((0..size).map(|i| Hash256::repeat_byte(i as u8)))
+            .unwrap();
+    list_hash256.tree_hash_root();
+
+    c.bench_with_input(
+        BenchmarkId::new("clone_list_u64", size),
+        &list_u64,
+        |b, list| {
+            b.iter(|| list.clone());
+        },
+    );
+    c.bench_with_input(
+        BenchmarkId::new("deep_clone_list_u64", size),
+        &list_u64,
+        |b, list| {
+            b.iter(|| list.deep_clone());
+        },
+    );
+    // Roundtripping through SSZ also produces an unshared list, but discards cached hashes.
+    c.bench_with_input(
+        BenchmarkId::new("ssz_roundtrip_list_u64", size),
+        &list_u64,
+        |b, list| {
+            b.iter(|| List::<u64, C>::from_ssz_bytes(&list.as_ssz_bytes()).unwrap());
+        },
+    );
+
+    c.bench_with_input(
+        BenchmarkId::new("clone_list_hash256", size),
+        &list_hash256,
+        |b, list| {
+            b.iter(|| list.clone());
+        },
+    );
+    c.bench_with_input(
+        BenchmarkId::new("deep_clone_list_hash256", size),
+        &list_hash256,
+        |b, list| {
+            b.iter(|| list.deep_clone());
+        },
+    );
+}
+
+criterion_group!(benches, clone_list);
+criterion_main!(benches);
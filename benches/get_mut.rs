@@ -0,0 +1,51 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use milhouse::List;
+use tree_hash::{Hash256, TreeHash};
+
+type C = typenum::U1099511627776;
+const N: u64 = 1_000_000;
+
+/// Edit a single element of a large hashed list, then re-hash it.
+///
+/// Only the path from the root to the edited leaf should be rebuilt and re-hashed, so this should
+/// take time logarithmic in the size of the list.
+pub fn get_mut_single(c: &mut Criterion) {
+    let size = N;
+
+    let list_u64 = List::<u64, C>::try_from_iter(0..size).unwrap();
+    list_u64.tree_hash_root();
+    let list_hash256 =
+        List::<Hash256, C>::try_from_iter((0..size).map(|i| Hash256::repeat_byte(i as u8)))
+            .unwrap();
+    list_hash256.tree_hash_root();
+
+    for index in [0, size as usize / 2, size as usize - 1] {
+        c.bench_with_input(
+            BenchmarkId::new("get_mut_list_u64", index),
+            &list_u64,
+            |b, list| {
+                b.iter(|| {
+                    let mut list = list.clone();
+                    *list.get_mut(index).unwrap() += 1;
+                    list.apply_updates().unwrap();
+                    list.tree_hash_root()
+                });
+            },
+        );
+        c.bench_with_input(
+            BenchmarkId::new("get_mut_list_hash256", index),
+            &list_hash256,
+            |b, list| {
+                b.iter(|| {
+                    let mut list = list.clone();
+                    *list.get_mut(index).unwrap() = Hash256::repeat_byte(0xff);
+                    list.apply_updates().unwrap();
+                    list.tree_hash_root()
+                });
+            },
+        );
+    }
+}
+
+criterion_group!(benches, get_mut_single);
+criterion_main!(benches);
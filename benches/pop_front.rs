@@ -1,5 +1,6 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use milhouse::{List, Value};
+use tree_hash::Hash256;
 use typenum::Unsigned;
 
 type C = typenum::U1099511627776;
@@ -57,5 +58,26 @@ pub fn pop_front_list_u64(c: &mut Criterion) {
     );
 }
 
-criterion_group!(benches, pop_front_list_u64);
+/// Unpacked values can always reuse whole leaves, but popping `n` elements shifts every remaining
+/// leaf by `n` positions. Internal nodes can only be reused when `n` is a multiple of their size,
+/// so ragged pops rebuild every internal node no matter how the tail is pushed.
+pub fn pop_front_list_hash256(c: &mut Criterion) {
+    let size = N;
+
+    let base_list =
+        List::<Hash256, C>::try_from_iter((0..size).map(|i| Hash256::repeat_byte(i as u8)))
+            .unwrap();
+
+    for n in [3, 4, 1024] {
+        c.bench_with_input(
+            BenchmarkId::new(format!("pop_front_hash256_{n}"), size),
+            &base_list,
+            |b, list| {
+                b.iter(|| pop_front(list, n));
+            },
+        );
+    }
+}
+
+criterion_group!(benches, pop_front_list_u64, pop_front_list_hash256);
 criterion_main!(benches);
@@ -2,6 +2,7 @@ use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use milhouse::{List, Value, Vector};
 use ssz::{Decode, Encode};
 use ssz_types::VariableList;
+use tree_hash::TreeHash;
 use typenum::Unsigned;
 
 type C = typenum::U1099511627776;
@@ -57,6 +58,17 @@ pub fn ssz(c: &mut Criterion) {
         b.iter(|| encode_list(l1));
     });
 
+    // Packed leaves with cached hashes are copied into the output without per-element encoding.
+    let hashed_list = list.clone();
+    hashed_list.tree_hash_root();
+    c.bench_with_input(
+        BenchmarkId::new("ssz_encode_list_hashed", size),
+        &hashed_list,
+        |b, l1| {
+            b.iter(|| encode_list(l1));
+        },
+    );
+
     c.bench_with_input(
         BenchmarkId::new("ssz_encode_decode_list", size),
         &list,
@@ -73,6 +85,15 @@ pub fn ssz(c: &mut Criterion) {
         },
     );
 
+    let vector_bytes = vector.as_ssz_bytes();
+    c.bench_with_input(
+        BenchmarkId::new("ssz_decode_vector", size),
+        &vector_bytes,
+        |b, bytes| {
+            b.iter(|| Vector::<u64, D>::from_ssz_bytes(bytes).unwrap());
+        },
+    );
+
     c.bench_with_input(
         BenchmarkId::new("ssz_encode_decode_vector", size),
         &vector,
@@ -68,5 +68,31 @@ pub fn tree_hash_root(c: &mut Criterion) {
     );
 }
 
-criterion_group!(benches, tree_hash_root);
+pub fn tree_hash_root_small(c: &mut Criterion) {
+    for size in [16, 256, 4096] {
+        c.bench_with_input(
+            BenchmarkId::new("tree_hash_root_small_list", size),
+            &size,
+            |b, &size| {
+                b.iter(|| {
+                    let l1 = List::<u64, C>::try_from_iter(0..size).unwrap();
+                    l1.tree_hash_root()
+                });
+            },
+        );
+
+        c.bench_with_input(
+            BenchmarkId::new("tree_hash_root_small_list_sequential", size),
+            &size,
+            |b, &size| {
+                b.iter(|| {
+                    let l1 = List::<u64, C>::try_from_iter(0..size).unwrap();
+                    l1.tree_hash_root_sequential().unwrap()
+                });
+            },
+        );
+    }
+}
+
+criterion_group!(benches, tree_hash_root, tree_hash_root_small);
 criterion_main!(benches);
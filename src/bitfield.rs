@@ -0,0 +1,143 @@
+use crate::utils::int_log;
+use crate::{Error, List};
+use tree_hash::{Hash256, PackedEncoding, TreeHash, BYTES_PER_CHUNK};
+use typenum::Unsigned;
+
+/// Persistent SSZ `Bitlist[N]`, holding up to `N` bits.
+///
+/// Bits are packed into bytes least-significant bit first and stored in a `List<u8, N>`, so
+/// clones share structure like any other list. Tree hashing follows the SSZ rules for bitlists:
+/// the bytes are merkleized up to the chunk limit for `N` bits, and the length in bits is mixed
+/// in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bitfield<N: Unsigned> {
+    bytes: List<u8, N>,
+    len: usize,
+}
+
+impl<N: Unsigned> Bitfield<N> {
+    pub fn new() -> Self {
+        Self {
+            bytes: List::empty(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the bit at `index`, erroring if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Result<bool, Error> {
+        if index >= self.len {
+            return Err(Error::OutOfBounds {
+                index,
+                len: self.len,
+            });
+        }
+        let byte = self.bytes.try_get(index / 8)?;
+        Ok(byte & (1 << (index % 8)) != 0)
+    }
+
+    /// Set the bit at `index`, erroring if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: bool) -> Result<(), Error> {
+        let len = self.len;
+        if index >= len {
+            return Err(Error::OutOfBounds { index, len });
+        }
+        let byte = self
+            .bytes
+            .get_mut(index / 8)
+            .ok_or(Error::OutOfBounds { index, len })?;
+        let mask = 1 << (index % 8);
+        if value {
+            *byte |= mask;
+        } else {
+            *byte &= !mask;
+        }
+        Ok(())
+    }
+
+    /// Append a bit, erroring if the bitfield already holds `N` bits.
+    pub fn push(&mut self, value: bool) -> Result<(), Error> {
+        if self.len >= N::to_usize() {
+            return Err(Error::ListFull { len: self.len });
+        }
+        if self.len % 8 == 0 {
+            self.bytes.push(u8::from(value))?;
+            self.len += 1;
+            Ok(())
+        } else {
+            self.len += 1;
+            self.set(self.len - 1, value)
+        }
+    }
+
+    /// Iterate the bits in index order.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        self.bytes
+            .iter()
+            .flat_map(|byte| (0..8).map(move |i| byte & (1 << i) != 0))
+            .take(self.len)
+    }
+
+    /// Count the bits which are set.
+    ///
+    /// Unused bits of the last byte are always zero, so this counts whole bytes.
+    pub fn num_set_bits(&self) -> usize {
+        self.bytes
+            .iter()
+            .map(|byte| byte.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn has_pending_updates(&self) -> bool {
+        self.bytes.has_pending_updates()
+    }
+
+    pub fn apply_updates(&mut self) -> Result<(), Error> {
+        self.bytes.apply_updates()
+    }
+
+    /// Depth of the SSZ merkle tree for `N` bits, in chunks.
+    fn chunk_depth() -> usize {
+        let bits_per_chunk = BYTES_PER_CHUNK * 8;
+        int_log(N::to_usize().div_ceil(bits_per_chunk))
+    }
+}
+
+impl<N: Unsigned> Default for Bitfield<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Unsigned> TreeHash for Bitfield<N> {
+    fn tree_hash_type() -> tree_hash::TreeHashType {
+        tree_hash::TreeHashType::List
+    }
+
+    fn tree_hash_packed_encoding(&self) -> PackedEncoding {
+        unreachable!("Bitfield should never be packed.")
+    }
+
+    fn tree_hash_packing_factor() -> usize {
+        unreachable!("Bitfield should never be packed.")
+    }
+
+    fn tree_hash_root(&self) -> Hash256 {
+        assert!(!self.has_pending_updates());
+
+        // The bytes are held in a deeper tree than the bitlist's, but the unused chunks are zero,
+        // so the root of the bitlist is the left-most subtree of the byte list.
+        let root = self
+            .bytes
+            .node_hash(0, Self::chunk_depth())
+            .expect("bitlist depth should not exceed byte list depth");
+        tree_hash::mix_in_length(&root, self.len)
+    }
+}
@@ -36,6 +36,66 @@ impl<T: Value> Builder<T> {
         }
     }
 
+    /// Create a builder which resumes building from the finished tree of a list.
+    ///
+    /// The complete subtrees on the left of the tree are reused as-is, so appending `k` values
+    /// and calling `finish` only builds `O(k + depth)` new nodes. The `depth` excludes the
+    /// packing depth, as for `new`.
+    pub fn from_tree(tree: Arc<Tree<T>>, depth: usize, length: Length) -> Result<Self, Error> {
+        let mut builder = Self::new(depth, 0)?;
+        let length = length.as_usize();
+
+        if length > builder.capacity {
+            return Err(Error::BuilderFull);
+        }
+        if tree.compute_depth() != depth {
+            return Err(Error::BuilderInvalidTree);
+        }
+
+        builder.length = Length(length);
+
+        if length == builder.capacity {
+            builder.stack.push(MaybeArced::Arced(tree));
+            return Ok(builder);
+        }
+
+        // Each complete subtree on the left of the path to the next index is a stack entry.
+        let full_leaves = length >> builder.packing_depth;
+        let partial_len = builder
+            .packing_factor
+            .map_or(0, |packing_factor| length % packing_factor);
+
+        let mut node = &tree;
+        for child_depth in (0..depth).rev() {
+            if full_leaves & ((1 << (child_depth + 1)) - 1) == 0 && partial_len == 0 {
+                break;
+            }
+            let Tree::Node { left, right, .. } = &**node else {
+                return Err(Error::BuilderInvalidTree);
+            };
+            if (full_leaves >> child_depth) & 1 == 1 {
+                builder.stack.push(MaybeArced::Arced(left.clone()));
+                node = right;
+            } else {
+                node = left;
+            }
+        }
+
+        // The partially-filled packed leaf must be unarced so that `push` can add to it.
+        if partial_len > 0 {
+            match &**node {
+                Tree::PackedLeaf(leaf) if leaf.values.len() == partial_len => {
+                    builder.stack.push(MaybeArced::Unarced(Tree::PackedLeaf(
+                        PackedLeaf::from_slice(&leaf.values)?,
+                    )));
+                }
+                _ => return Err(Error::BuilderInvalidTree),
+            }
+        }
+
+        Ok(builder)
+    }
+
     pub fn push(&mut self, value: T) -> Result<(), Error> {
         if self.length.as_usize() == self.capacity {
             return Err(Error::BuilderFull);
@@ -73,6 +133,67 @@ impl<T: Value> Builder<T> {
         Ok(())
     }
 
+    /// Push all of `values` onto the end of the tree.
+    ///
+    /// For packed types, full chunks are copied directly into new packed leaves, so stack
+    /// merging only happens once per leaf rather than once per value. The resulting tree is
+    /// identical to the one produced by calling `push` for each value.
+    ///
+    /// Nothing is pushed if `values` would not fit in the remaining capacity.
+    pub fn push_many(&mut self, values: &[T]) -> Result<(), Error> {
+        if values.len() > self.capacity - self.length.as_usize() {
+            return Err(Error::BuilderFull);
+        }
+
+        let Some(packing_factor) = self.packing_factor else {
+            for value in values {
+                self.push(value.clone())?;
+            }
+            return Ok(());
+        };
+
+        // Fill the current partially-filled leaf, if any.
+        let unaligned = (packing_factor - self.length.as_usize() % packing_factor) % packing_factor;
+        let (head, tail) = values.split_at(unaligned.min(values.len()));
+        for value in head {
+            self.push(value.clone())?;
+        }
+
+        let mut chunks = tail.chunks_exact(packing_factor);
+        for chunk in &mut chunks {
+            self.push_full_leaf(chunk)?;
+        }
+
+        for value in chunks.remainder() {
+            self.push(value.clone())?;
+        }
+
+        Ok(())
+    }
+
+    /// Push a full packed leaf containing `values`, which must start at a leaf boundary.
+    fn push_full_leaf(&mut self, values: &[T]) -> Result<(), Error> {
+        let next_index = self.length.as_usize() + values.len();
+
+        let mut new_stack_top =
+            MaybeArced::Unarced(Tree::PackedLeaf(PackedLeaf::from_slice(values)?));
+
+        let values_to_merge = next_index
+            .trailing_zeros()
+            .saturating_sub(self.packing_depth as u32);
+
+        for _ in 0..values_to_merge {
+            let left = self.stack.pop().ok_or(Error::BuilderStackEmptyMerge)?;
+            new_stack_top =
+                MaybeArced::Unarced(Tree::node_unboxed(left.arced(), new_stack_top.arced()));
+        }
+
+        self.stack.push(new_stack_top);
+        *self.length.as_mut() += values.len();
+
+        Ok(())
+    }
+
     pub fn push_node(&mut self, node: Arc<Tree<T>>, len: usize) -> Result<(), Error> {
         if self.length.as_usize() == self.capacity {
             return Err(Error::BuilderFull);
@@ -107,6 +228,20 @@ impl<T: Value> Builder<T> {
         Ok(())
     }
 
+    /// Finish the tree, erroring if exactly `expected_len` values have not been pushed.
+    ///
+    /// This is intended for vectors, which must be full. The expected length is passed
+    /// explicitly because a vector's length need not be a power of two.
+    pub fn finish_exact(self, expected_len: usize) -> Result<(Arc<Tree<T>>, usize, Length), Error> {
+        if self.length.as_usize() != expected_len {
+            return Err(Error::WrongVectorLength {
+                len: self.length.as_usize(),
+                expected: expected_len,
+            });
+        }
+        self.finish()
+    }
+
     pub fn finish(mut self) -> Result<(Arc<Tree<T>>, usize, Length), Error> {
         if self.stack.is_empty() {
             return Ok((Tree::zero(self.depth), self.depth, Length(0)));
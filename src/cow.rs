@@ -5,6 +5,7 @@ use std::ops::Deref;
 pub enum Cow<'a, T: Clone> {
     BTree(BTreeCow<'a, T>),
     Vec(VecCow<'a, T>),
+    Dense(DenseCow<'a, T>),
 }
 
 impl<T: Clone> Deref for Cow<'_, T> {
@@ -14,6 +15,7 @@ impl<T: Clone> Deref for Cow<'_, T> {
         match self {
             Self::BTree(cow) => cow.deref(),
             Self::Vec(cow) => cow.deref(),
+            Self::Dense(cow) => cow.deref(),
         }
     }
 }
@@ -23,6 +25,7 @@ impl<'a, T: Clone> Cow<'a, T> {
         match self {
             Self::BTree(cow) => cow.into_mut(),
             Self::Vec(cow) => cow.into_mut(),
+            Self::Dense(cow) => cow.into_mut(),
         }
     }
 
@@ -30,6 +33,16 @@ impl<'a, T: Clone> Cow<'a, T> {
         match self {
             Self::BTree(cow) => cow.make_mut(),
             Self::Vec(cow) => cow.make_mut(),
+            Self::Dense(cow) => cow.make_mut(),
+        }
+    }
+
+    /// Return `true` if the value is already mutable, i.e. `make_mut` will not clone it.
+    pub fn is_mutable(&self) -> bool {
+        match self {
+            Self::BTree(cow) => cow.is_mutable(),
+            Self::Vec(cow) => cow.is_mutable(),
+            Self::Dense(cow) => cow.is_mutable(),
         }
     }
 }
@@ -38,6 +51,9 @@ pub trait CowTrait<'a, T: Clone>: Deref<Target = T> {
     fn into_mut(self) -> Result<&'a mut T, Error>;
 
     fn make_mut(&mut self) -> Result<&mut T, Error>;
+
+    /// Return `true` if the value is already mutable, i.e. `make_mut` will not clone it.
+    fn is_mutable(&self) -> bool;
 }
 
 pub enum BTreeCow<'a, T: Clone> {
@@ -75,6 +91,10 @@ impl<'a, T: Clone> CowTrait<'a, T> for BTreeCow<'a, T> {
             }
         }
     }
+
+    fn is_mutable(&self) -> bool {
+        matches!(self, Self::Mutable { .. })
+    }
 }
 
 impl<T: Clone> Deref for BTreeCow<'_, T> {
@@ -123,6 +143,10 @@ impl<'a, T: Clone> CowTrait<'a, T> for VecCow<'a, T> {
             }
         }
     }
+
+    fn is_mutable(&self) -> bool {
+        matches!(self, Self::Mutable { .. })
+    }
 }
 
 impl<T: Clone> Deref for VecCow<'_, T> {
@@ -135,3 +159,68 @@ impl<T: Clone> Deref for VecCow<'_, T> {
         }
     }
 }
+
+pub enum DenseCow<'a, T: Clone> {
+    Immutable {
+        value: &'a T,
+        entry: Option<DenseVacantEntry<'a, T>>,
+    },
+    Mutable {
+        value: &'a mut T,
+    },
+}
+
+/// Vacant slot of a `DenseUpdateMap`, along with the map's length which is incremented on insert.
+pub struct DenseVacantEntry<'a, T> {
+    pub(crate) slot: &'a mut Option<T>,
+    pub(crate) len: &'a mut usize,
+}
+
+impl<'a, T> DenseVacantEntry<'a, T> {
+    pub fn insert(self, value: T) -> &'a mut T {
+        *self.len += 1;
+        self.slot.insert(value)
+    }
+}
+
+impl<'a, T: Clone> CowTrait<'a, T> for DenseCow<'a, T> {
+    fn into_mut(self) -> Result<&'a mut T, Error> {
+        match self {
+            Self::Immutable { value, entry } => entry
+                .ok_or(Error::CowMissingEntry)
+                .map(|e| e.insert(value.clone())),
+            Self::Mutable { value } => Ok(value),
+        }
+    }
+
+    fn make_mut(&mut self) -> Result<&mut T, Error> {
+        match self {
+            Self::Mutable { value } => Ok(value),
+            Self::Immutable { entry, value } => {
+                let value_mut_ref = entry
+                    .take()
+                    .ok_or(Error::CowMissingEntry)?
+                    .insert(value.clone());
+                *self = Self::Mutable {
+                    value: value_mut_ref,
+                };
+                self.make_mut()
+            }
+        }
+    }
+
+    fn is_mutable(&self) -> bool {
+        matches!(self, Self::Mutable { .. })
+    }
+}
+
+impl<T: Clone> Deref for DenseCow<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self {
+            Self::Immutable { value, .. } => value,
+            Self::Mutable { value } => value,
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{Arc, Error, List, Tree, UpdateMap, Value, Vector};
+use std::collections::{hash_map::Entry, HashMap};
+use tree_hash::Hash256;
+use typenum::Unsigned;
+
+/// Interner for subtrees which deduplicates identical subtrees across many lists and vectors.
+///
+/// Subtrees are keyed by their depth, the number of elements they contain, and their tree hash.
+/// The number of elements is required because a subtree padded with zeros has the same hash as a
+/// subtree containing zero-valued elements.
+#[derive(Debug)]
+pub struct Deduplicator<T: Value> {
+    known_subtrees: HashMap<(usize, usize, Hash256), Arc<Tree<T>>>,
+    /// Lengths of the subtrees in `known_subtrees`, keyed by their address.
+    interned: HashMap<usize, usize>,
+}
+
+impl<T: Value> Default for Deduplicator<T> {
+    fn default() -> Self {
+        Self {
+            known_subtrees: HashMap::new(),
+            interned: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Value> Deduplicator<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of distinct subtrees known to this deduplicator.
+    pub fn len(&self) -> usize {
+        self.known_subtrees.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.known_subtrees.is_empty()
+    }
+}
+
+impl<T: Value + Send + Sync> Deduplicator<T> {
+    /// Replace the subtrees of `list` by identical subtrees seen previously.
+    ///
+    /// The list must not have pending updates. Its tree will be fully hashed.
+    pub fn rebase<N: Unsigned, U: UpdateMap<T>>(
+        &mut self,
+        list: &mut List<T, N, U>,
+    ) -> Result<(), Error> {
+        if list.has_pending_updates() {
+            return Err(Error::DeduplicatePendingUpdates);
+        }
+        let backing = &mut list.interface.backing;
+        backing.tree.tree_hash();
+        let (tree, _) = self.intern(&backing.tree, backing.depth)?;
+        backing.tree = tree;
+        Ok(())
+    }
+
+    /// Replace the subtrees of `vector` by identical subtrees seen previously.
+    ///
+    /// The vector must not have pending updates. Its tree will be fully hashed.
+    pub fn rebase_vector<N: Unsigned, U: UpdateMap<T>>(
+        &mut self,
+        vector: &mut Vector<T, N, U>,
+    ) -> Result<(), Error> {
+        if vector.has_pending_updates() {
+            return Err(Error::DeduplicatePendingUpdates);
+        }
+        let backing = &mut vector.interface.backing;
+        backing.tree.tree_hash();
+        let (tree, _) = self.intern(&backing.tree, backing.depth)?;
+        backing.tree = tree;
+        Ok(())
+    }
+
+    fn intern(
+        &mut self,
+        node: &Arc<Tree<T>>,
+        depth: usize,
+    ) -> Result<(Arc<Tree<T>>, usize), Error> {
+        if let Some(len) = self.interned.get(&address(node)) {
+            return Ok((node.clone(), *len));
+        }
+
+        let (new_node, len) = match &**node {
+            Tree::Zero(_) => return Ok((node.clone(), 0)),
+            Tree::Leaf(_) => (node.clone(), 1),
+            Tree::PackedLeaf(leaf) => (node.clone(), leaf.values.len()),
+            Tree::Node { hash, left, right } if depth > 0 => {
+                let (new_left, left_len) = self.intern(left, depth - 1)?;
+                let (new_right, right_len) = self.intern(right, depth - 1)?;
+                let new_node = if Arc::ptr_eq(&new_left, left) && Arc::ptr_eq(&new_right, right) {
+                    node.clone()
+                } else {
+                    Tree::node(new_left, new_right, *hash.read())
+                };
+                (new_node, left_len + right_len)
+            }
+            Tree::Node { .. } => return Err(Error::InvalidRebaseNode),
+        };
+
+        match self
+            .known_subtrees
+            .entry((depth, len, new_node.tree_hash()))
+        {
+            Entry::Occupied(entry) => Ok((entry.get().clone(), len)),
+            Entry::Vacant(entry) => {
+                self.interned.insert(address(&new_node), len);
+                entry.insert(new_node.clone());
+                Ok((new_node, len))
+            }
+        }
+    }
+}
+
+fn address<T>(arc: &Arc<T>) -> usize {
+    &**arc as *const T as usize
+}
@@ -1,9 +1,11 @@
 use std::fmt::{Display, Error as FmtError, Formatter};
+use tree_hash::Hash256;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Error {
     OutOfBoundsUpdate { index: usize, len: usize },
     OutOfBoundsIterFrom { index: usize, len: usize },
+    OutOfBounds { index: usize, len: usize },
     ListFull { len: usize },
     PackedLeafFull { len: usize },
     LeafUpdateMissing { index: usize },
@@ -17,6 +19,7 @@ pub enum Error {
     UpdateLeavesError,
     InvalidRebaseNode,
     InvalidRebaseLeaf,
+    RebaseDepthMismatch { depth: usize, base_depth: usize },
     BuilderInvalidDepth { depth: usize },
     BuilderExpectedLeaf,
     BuilderStackEmptyMerge,
@@ -28,13 +31,126 @@ pub enum Error {
     BuilderStackEmptyFinalize,
     BuilderStackLeftover,
     BuilderFull,
+    BuilderInvalidTree,
     BulkUpdateUnclean,
     CowMissingEntry,
     LevelIterPendingUpdates,
+    TreeHashPendingUpdates,
+    DeduplicatePendingUpdates,
+    PendingUpdateHole { index: usize, len: usize },
+    ZipPendingUpdates,
+    DiffPendingUpdates,
+    FoldPendingUpdates,
+    ChunkLeavesPendingUpdates,
+    SszReaderVariableLength,
+    SszReaderIo { kind: std::io::ErrorKind },
+    ArcIterPacked,
+    SszDecode { error: ssz::DecodeError },
+    PackedLeafUpdateGap { index: usize, next_index: usize },
+    ChunkLeavesNotPacked,
+    RootMismatch { expected: Hash256, actual: Hash256 },
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
-        write!(f, "{:?}", self)
+        match self {
+            Self::OutOfBoundsUpdate { index, len } => {
+                write!(
+                    f,
+                    "update at index {index} is out of bounds for length {len}"
+                )
+            }
+            Self::OutOfBoundsIterFrom { index, len } => write!(
+                f,
+                "cannot iterate from index {index}, which is out of bounds for length {len}"
+            ),
+            Self::OutOfBounds { index, len } => {
+                write!(f, "index {index} is out of bounds for length {len}")
+            }
+            Self::ListFull { len } => write!(f, "list is full at length {len}"),
+            Self::PackedLeafFull { len } => write!(f, "packed leaf is full at length {len}"),
+            Self::LeafUpdateMissing { index } => {
+                write!(f, "missing update for leaf at index {index}")
+            }
+            Self::PackedLeafOutOfBounds { sub_index, len } => write!(
+                f,
+                "packed leaf index {sub_index} is out of bounds for packed leaf of length {len}"
+            ),
+            Self::NodeUpdatesMissing { prefix } => {
+                write!(f, "missing updates for node with prefix {prefix}")
+            }
+            Self::InvalidListUpdate => write!(f, "invalid list update"),
+            Self::InvalidVectorUpdate => write!(f, "invalid vector update"),
+            Self::WrongVectorLength { len, expected } => {
+                write!(f, "wrong vector length {len}, expected {expected}")
+            }
+            Self::PushNotSupported => write!(f, "push is not supported"),
+            Self::UpdateLeafError => write!(f, "failed to update leaf"),
+            Self::UpdateLeavesError => write!(f, "failed to update leaves"),
+            Self::InvalidRebaseNode => write!(f, "invalid node encountered while rebasing"),
+            Self::InvalidRebaseLeaf => write!(f, "invalid leaf encountered while rebasing"),
+            Self::RebaseDepthMismatch { depth, base_depth } => write!(
+                f,
+                "cannot rebase tree of depth {depth} on tree of depth {base_depth}"
+            ),
+            Self::BuilderInvalidDepth { depth } => {
+                write!(f, "invalid depth {depth} for builder")
+            }
+            Self::BuilderExpectedLeaf => write!(f, "builder expected a packed leaf"),
+            Self::BuilderStackEmptyMerge => write!(f, "builder stack empty while merging"),
+            Self::BuilderStackEmptyMergeLeft => {
+                write!(f, "builder stack empty while merging left node")
+            }
+            Self::BuilderStackEmptyMergeRight => {
+                write!(f, "builder stack empty while merging right node")
+            }
+            Self::BuilderStackEmptyFinish => write!(f, "builder stack empty while finishing"),
+            Self::BuilderStackEmptyFinishLeft => {
+                write!(f, "builder stack empty while finishing left node")
+            }
+            Self::BuilderStackEmptyFinishRight => {
+                write!(f, "builder stack empty while finishing right node")
+            }
+            Self::BuilderStackEmptyFinalize => write!(f, "builder stack empty while finalizing"),
+            Self::BuilderStackLeftover => write!(f, "builder stack not empty after finalizing"),
+            Self::BuilderFull => write!(f, "builder is full"),
+            Self::BuilderInvalidTree => write!(f, "tree is invalid for builder"),
+            Self::BulkUpdateUnclean => write!(f, "cannot bulk update with pending updates"),
+            Self::CowMissingEntry => write!(f, "copy-on-write entry is missing"),
+            Self::LevelIterPendingUpdates => {
+                write!(f, "cannot iterate levels with pending updates")
+            }
+            Self::TreeHashPendingUpdates => write!(f, "cannot tree hash with pending updates"),
+            Self::DeduplicatePendingUpdates => {
+                write!(f, "cannot deduplicate with pending updates")
+            }
+            Self::PendingUpdateHole { index, len } => write!(
+                f,
+                "discarding pending push at index {index} leaves a hole in list of length {len}"
+            ),
+            Self::ZipPendingUpdates => write!(f, "cannot zip lists with pending updates"),
+            Self::DiffPendingUpdates => write!(f, "cannot diff lists with pending updates"),
+            Self::FoldPendingUpdates => write!(f, "cannot fold with pending updates"),
+            Self::ChunkLeavesPendingUpdates => {
+                write!(f, "cannot iterate packed leaves with pending updates")
+            }
+            Self::SszReaderVariableLength => {
+                write!(f, "cannot read variable-length items from SSZ reader")
+            }
+            Self::SszReaderIo { kind } => write!(f, "I/O error reading SSZ: {kind}"),
+            Self::ArcIterPacked => write!(f, "packed values are not stored in arcs"),
+            Self::SszDecode { error } => write!(f, "error decoding SSZ: {error:?}"),
+            Self::PackedLeafUpdateGap { index, next_index } => write!(
+                f,
+                "packed leaf update at index {index} leaves a gap at index {next_index}"
+            ),
+            Self::ChunkLeavesNotPacked => write!(f, "only packed values are stored in chunks"),
+            Self::RootMismatch { expected, actual } => write!(
+                f,
+                "tree hash root {actual:?} does not match expected {expected:?}"
+            ),
+        }
     }
 }
+
+impl std::error::Error for Error {}
@@ -9,6 +9,7 @@ use crate::{
 use arbitrary::Arbitrary;
 use std::collections::BTreeMap;
 use std::marker::PhantomData;
+use std::ops::ControlFlow;
 use tree_hash::Hash256;
 
 pub trait ImmList<T: Value> {
@@ -20,6 +21,17 @@ pub trait ImmList<T: Value> {
         self.len().as_usize() == 0
     }
 
+    /// Get the values at each of the sorted and deduplicated `indices`, paired with their
+    /// indices. Out of bounds indices are skipped.
+    ///
+    /// The default implementation looks up each index separately.
+    fn get_many(&self, indices: &[usize]) -> Vec<(usize, &T)> {
+        indices
+            .iter()
+            .filter_map(|&index| Some((index, self.get(index)?)))
+            .collect()
+    }
+
     fn iter_from(&self, index: usize) -> Iter<T>;
 
     fn level_iter_from(&self, index: usize) -> LevelIter<T>;
@@ -33,6 +45,9 @@ pub trait MutList<T: Value>: ImmList<T> {
         updates: U,
         hash_updates: Option<BTreeMap<(usize, usize), Hash256>>,
     ) -> Result<(), Error>;
+    fn update_par<U: UpdateMap<T> + Sync>(&mut self, updates: U) -> Result<(), Error>
+    where
+        T: Send + Sync;
 }
 
 #[derive(Debug, PartialEq, Clone, Arbitrary)]
@@ -70,6 +85,42 @@ where
             .get_mut_with(idx, |idx| self.backing.get(idx).cloned())
     }
 
+    pub fn update_each(
+        &mut self,
+        indices: &[usize],
+        mut f: impl FnMut(usize, &mut T),
+    ) -> Result<(), Error> {
+        let len = self.len();
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+
+        if let Some(&index) = indices.last().filter(|&&index| index >= len) {
+            return Err(Error::OutOfBounds { index, len });
+        }
+
+        // Fetch the backing values of all indices without pending updates in one traversal.
+        let missing = indices
+            .iter()
+            .copied()
+            .filter(|&index| self.updates.get(index).is_none())
+            .collect::<Vec<_>>();
+        let mut backing_values = self.backing.get_many(&missing).into_iter().peekable();
+
+        for index in indices {
+            // Both are sorted, so the backing value for `index` is next if it is needed.
+            let backing_value = backing_values
+                .next_if(|&(i, _)| i == index)
+                .map(|(_, value)| value);
+            let value = self
+                .updates
+                .get_mut_with(index, |_| backing_value.cloned())
+                .ok_or(Error::OutOfBounds { index, len })?;
+            f(index, value);
+        }
+        Ok(())
+    }
+
     pub fn get_cow(&mut self, index: usize) -> Option<Cow<T>> {
         self.updates
             .get_cow_with(index, |idx| self.backing.get(idx))
@@ -92,10 +143,72 @@ where
         }
     }
 
+    /// Apply the pending updates, updating large subtrees in parallel.
+    pub fn apply_updates_par(&mut self) -> Result<(), Error>
+    where
+        T: Send + Sync,
+        U: Sync,
+    {
+        if !self.updates.is_empty() {
+            let updates = std::mem::take(&mut self.updates);
+            self.backing.update_par(updates)
+        } else {
+            Ok(())
+        }
+    }
+
     pub fn has_pending_updates(&self) -> bool {
         !self.updates.is_empty()
     }
 
+    pub fn pending_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.updates.keys()
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.updates.len()
+    }
+
+    /// Discard the pending update for `index`, returning it if present.
+    ///
+    /// Errors if `index` was pushed and is not the last index, as discarding it would leave a
+    /// hole in the list.
+    pub fn discard_pending(&mut self, index: usize) -> Result<Option<T>, Error> {
+        let len = self.len();
+        if index >= self.backing.len().as_usize() && index + 1 < len {
+            return Err(Error::PendingUpdateHole { index, len });
+        }
+        Ok(self.updates.remove(index))
+    }
+
+    /// Retain only the pending updates for which `f` returns `true`.
+    ///
+    /// Errors without discarding anything if a pushed index would be discarded while a later
+    /// pushed index is retained, as that would leave a hole in the list.
+    pub fn retain_pending(&mut self, mut f: impl FnMut(usize, &T) -> bool) -> Result<(), Error> {
+        let len = self.len();
+        let backing_len = self.backing.len().as_usize();
+        let mut discard = vec![];
+        let mut first_discarded_push = None;
+        self.updates.for_each_range(0, len, |index, value| {
+            if f(index, value) {
+                if let Some(index) = first_discarded_push {
+                    return ControlFlow::Continue(Err(Error::PendingUpdateHole { index, len }));
+                }
+            } else {
+                if index >= backing_len {
+                    first_discarded_push.get_or_insert(index);
+                }
+                discard.push(index);
+            }
+            ControlFlow::Continue(Ok(()))
+        })?;
+        for index in discard {
+            self.updates.remove(index);
+        }
+        Ok(())
+    }
+
     pub fn iter(&self) -> InterfaceIter<T, U> {
         self.iter_from(0)
     }
@@ -109,6 +222,16 @@ where
         }
     }
 
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        // Copy every element that isn't already in the update map.
+        for (idx, value) in self.backing.iter_from(0).enumerate() {
+            if self.updates.get(idx).is_none() {
+                self.updates.insert(idx, value.clone());
+            }
+        }
+        self.updates.iter_mut().map(|(_, value)| value)
+    }
+
     pub fn iter_cow(&mut self) -> InterfaceIterCow<T, U> {
         let index = 0;
         InterfaceIterCow {
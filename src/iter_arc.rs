@@ -0,0 +1,120 @@
+use crate::{utils::opt_packing_factor, Arc, Error, Leaf, Tree, UpdateMap, Value};
+
+/// Iterator over the `Arc`s holding the values of a tree of unpacked leaves.
+#[derive(Debug)]
+pub struct ArcIter<'a, T: Value> {
+    /// Stack of tree nodes corresponding to the current position.
+    stack: Vec<&'a Tree<T>>,
+    /// The list index corresponding to the current position (next element to be yielded).
+    index: usize,
+    /// The `depth` of the root tree.
+    full_depth: usize,
+    /// Number of items that will be yielded by the iterator.
+    length: usize,
+}
+
+impl<'a, T: Value> ArcIter<'a, T> {
+    /// Iterate from `index` onwards.
+    ///
+    /// Errors if `T` is packed, because packed values are not stored in `Arc`s.
+    pub fn from_index(
+        index: usize,
+        root: &'a Tree<T>,
+        depth: usize,
+        length: usize,
+    ) -> Result<Self, Error> {
+        if opt_packing_factor::<T>().is_some() {
+            return Err(Error::ArcIterPacked);
+        }
+        let mut stack = Vec::with_capacity(depth);
+        stack.push(root);
+
+        Ok(ArcIter {
+            stack,
+            index,
+            full_depth: depth,
+            length,
+        })
+    }
+}
+
+impl<'a, T: Value> Iterator for ArcIter<'a, T> {
+    type Item = &'a Arc<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.length {
+            return None;
+        }
+
+        loop {
+            match self.stack.last() {
+                None | Some(Tree::Zero(_)) | Some(Tree::PackedLeaf(_)) => return None,
+                Some(Tree::Leaf(Leaf { value, .. })) => {
+                    self.index += 1;
+
+                    // Backtrack to the parent node of the next subtree
+                    for _ in 0..=self.index.trailing_zeros() {
+                        self.stack.pop();
+                    }
+
+                    return Some(value);
+                }
+                Some(Tree::Node { left, right, .. }) => {
+                    let depth = self.full_depth - self.stack.len();
+
+                    if (self.index >> depth) & 1 == 0 {
+                        self.stack.push(left);
+                    } else {
+                        self.stack.push(right);
+                    }
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.length.saturating_sub(self.index);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: Value> ExactSizeIterator for ArcIter<'_, T> {}
+
+/// Iterator over the values of a list or vector wrapped in `Arc`s, including pending updates.
+///
+/// Values from the tree are shared with it, while pending updates are cloned into new `Arc`s.
+#[derive(Debug)]
+pub struct ArcInterfaceIter<'a, T: Value, U: UpdateMap<T>> {
+    pub(crate) tree_iter: ArcIter<'a, T>,
+    pub(crate) updates: &'a U,
+    pub(crate) index: usize,
+    pub(crate) length: usize,
+}
+
+impl<T: Value, U: UpdateMap<T>> Iterator for ArcInterfaceIter<'_, T, U> {
+    type Item = Arc<T>;
+
+    fn next(&mut self) -> Option<Arc<T>> {
+        if self.index >= self.length {
+            return None;
+        }
+        let index = self.index;
+        self.index += 1;
+
+        // Advance the tree iterator so that it moves in step with this iterator.
+        let backing_value = self.tree_iter.next();
+
+        // Prioritise the value from the update map.
+        match self.updates.get(index) {
+            Some(value) => Some(Arc::new(value.clone())),
+            None => backing_value.cloned(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.length.saturating_sub(self.index);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: Value, U: UpdateMap<T>> ExactSizeIterator for ArcInterfaceIter<'_, T, U> {}
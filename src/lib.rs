@@ -1,12 +1,15 @@
 #![allow(clippy::comparison_chain)]
 #![deny(clippy::unwrap_used)]
 
+pub mod bitfield;
 pub mod builder;
 pub mod cow;
+pub mod dedup;
 pub mod error;
 pub mod interface;
 pub mod interface_iter;
 pub mod iter;
+pub mod iter_arc;
 pub mod leaf;
 pub mod level_iter;
 pub mod list;
@@ -19,7 +22,9 @@ pub mod update_map;
 pub mod utils;
 pub mod vector;
 
+pub use bitfield::Bitfield;
 pub use cow::Cow;
+pub use dedup::Deduplicator;
 pub use error::Error;
 pub use interface::ImmList;
 pub use leaf::Leaf;
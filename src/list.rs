@@ -2,18 +2,25 @@ use crate::builder::Builder;
 use crate::interface::{ImmList, Interface, MutList};
 use crate::interface_iter::{InterfaceIter, InterfaceIterCow};
 use crate::iter::Iter;
+use crate::iter_arc::{ArcInterfaceIter, ArcIter};
 use crate::level_iter::{LevelIter, LevelNode};
 use crate::serde::ListVisitor;
-use crate::tree::RebaseAction;
+use crate::tree::{RebaseAction, RebaseStats, TreeStats};
 use crate::update_map::MaxMap;
-use crate::utils::{arb_arc, compute_level, int_log, opt_packing_depth, updated_length, Length};
+use crate::utils::{
+    arb_arc, checked_updated_length, compute_level, int_log, opt_packing_depth,
+    ssz_is_packed_encoding, Length,
+};
 use crate::{Arc, Cow, Error, Tree, UpdateMap, Value};
 use arbitrary::Arbitrary;
 use educe::Educe;
-use itertools::process_results;
+use itertools::{process_results, Itertools};
 use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
 use ssz::{Decode, Encode, SszEncoder, TryFromIter, BYTES_PER_LENGTH_OFFSET};
-use std::collections::BTreeMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::io::Read;
 use std::marker::PhantomData;
 use tree_hash::{Hash256, PackedEncoding, TreeHash};
 use typenum::Unsigned;
@@ -27,8 +34,16 @@ pub struct List<T: Value, N: Unsigned, U: UpdateMap<T> = MaxMap<VecMap<T>>> {
     pub(crate) interface: Interface<T, ListInner<T, N>, U>,
 }
 
-#[derive(Debug, Clone, Educe, Arbitrary)]
-#[educe(PartialEq(bound(T: Value, N: Unsigned)))]
+/// A saved state of a `List`, which can be restored with `List::restore`.
+///
+/// Snapshots never have pending updates and share their tree with the list they were taken from,
+/// so they are cheap to take and to hold.
+#[derive(Debug, Clone)]
+pub struct Snapshot<T: Value, N: Unsigned, U: UpdateMap<T> = MaxMap<VecMap<T>>> {
+    list: List<T, N, U>,
+}
+
+#[derive(Debug, Clone, Arbitrary)]
 #[arbitrary(bound = "T: Arbitrary<'arbitrary> + Value, N: Unsigned")]
 pub struct ListInner<T: Value, N: Unsigned> {
     #[arbitrary(with = arb_arc)]
@@ -69,6 +84,40 @@ impl<T: Value, N: Unsigned, U: UpdateMap<T>> List<T, N, U> {
         crate::repeat::repeat_list(elem, n)
     }
 
+    /// Create a list of length `n` where the element at each index `i` is `f(i)`.
+    pub fn from_fn(n: usize, f: impl FnMut(usize) -> T) -> Result<Self, Error> {
+        Self::try_from_iter((0..n).map(f))
+    }
+
+    /// Set every element of the list to `value`, keeping the current length.
+    ///
+    /// Any pending updates are discarded, and the tree is rebuilt using `repeat`, which shares
+    /// identical subtrees.
+    pub fn fill(&mut self, value: T) -> Result<(), Error> {
+        *self = Self::repeat(value, self.len())?;
+        Ok(())
+    }
+
+    /// Remove consecutive repeated elements, keeping the first of each run, like `Vec::dedup`.
+    ///
+    /// Pending updates are applied first, and the tree is rebuilt from the kept elements.
+    pub fn dedup(&mut self) -> Result<(), Error> {
+        self.apply_updates()?;
+
+        let mut builder = Self::builder()?;
+        let mut last = None;
+        for item in self.iter() {
+            if last != Some(item) {
+                builder.push(item.clone())?;
+                last = Some(item);
+            }
+        }
+
+        let (tree, depth, length) = builder.finish()?;
+        *self = Self::from_parts(tree, depth, length);
+        Ok(())
+    }
+
     pub fn repeat_slow(elem: T, n: usize) -> Result<Self, Error> {
         Self::try_from_iter(std::iter::repeat(elem).take(n))
     }
@@ -78,20 +127,82 @@ impl<T: Value, N: Unsigned, U: UpdateMap<T>> List<T, N, U> {
     }
 
     pub fn try_from_iter(iter: impl IntoIterator<Item = T>) -> Result<Self, Error> {
-        let mut builder = Self::builder()?;
+        Self::try_from_iter_reporting(iter).map_err(|(e, _)| e)
+    }
 
-        for item in iter.into_iter() {
-            builder.push(item)?;
+    /// Build a list from an iterator, reporting the index at which building stopped on error.
+    ///
+    /// If the iterator yields more than `N` elements the index is `N`, i.e. the index of the
+    /// first element that does not fit. No elements beyond it are consumed.
+    pub fn try_from_iter_reporting(
+        iter: impl IntoIterator<Item = T>,
+    ) -> Result<Self, (Error, usize)> {
+        let max_len = N::to_usize();
+        let iter = iter.into_iter();
+        // Fail fast if the iterator knows its exact length and it is too long.
+        if let (lower, Some(upper)) = iter.size_hint() {
+            if lower == upper && lower > max_len {
+                return Err((Error::BuilderFull, max_len));
+            }
+        }
+        let mut builder = Self::builder().map_err(|e| (e, 0))?;
+
+        let mut len = 0;
+        for item in iter {
+            // Check the length to cover the case where the capacity implied by packing_depth is
+            // greater than N. E.g. the builder might pack up to 32 u8s, even if N is < 32.
+            if len == max_len {
+                return Err((Error::BuilderFull, len));
+            }
+            builder.push(item).map_err(|e| (e, len))?;
+            len += 1;
         }
 
-        let (tree, depth, length) = builder.finish()?;
+        let (tree, depth, length) = builder.finish().map_err(|e| (e, len))?;
+
+        Ok(Self::from_parts(tree, depth, length))
+    }
 
-        // Check the length to cover the case where the capacity implied by packing_depth is
-        // greater than N. E.g. the builder might pack up to 32 u8s, even if N is < 32.
-        if length.as_usize() > N::to_usize() {
-            return Err(Error::BuilderFull);
+    /// Decode a list of fixed-length items from the SSZ bytes produced by `reader`.
+    ///
+    /// Items are decoded and pushed into a builder one at a time, so the encoded list is never
+    /// held in memory in full. The reader is read one item at a time, so it should be buffered.
+    /// Lists of variable-length items are not supported.
+    pub fn from_ssz_reader<R: Read>(mut reader: R) -> Result<Self, Error> {
+        if !<T as Decode>::is_ssz_fixed_len() {
+            return Err(Error::SszReaderVariableLength);
+        }
+        let item_len = <T as Decode>::ssz_fixed_len();
+        if item_len == 0 {
+            return Err(Error::SszDecode {
+                error: ssz::DecodeError::ZeroLengthItem,
+            });
         }
 
+        let mut builder = Self::builder()?;
+        let mut buf = vec![0; item_len];
+        let mut len = 0;
+        loop {
+            let bytes_read = read_up_to(&mut reader, &mut buf)?;
+            if bytes_read == 0 {
+                break;
+            } else if bytes_read < item_len {
+                return Err(Error::SszDecode {
+                    error: ssz::DecodeError::InvalidByteLength {
+                        len: bytes_read,
+                        expected: item_len,
+                    },
+                });
+            }
+            if len == N::to_usize() {
+                return Err(Error::ListFull { len });
+            }
+            let item = T::from_ssz_bytes(&buf).map_err(|error| Error::SszDecode { error })?;
+            builder.push(item)?;
+            len += 1;
+        }
+
+        let (tree, depth, length) = builder.finish()?;
         Ok(Self::from_parts(tree, depth, length))
     }
 
@@ -109,10 +220,74 @@ impl<T: Value, N: Unsigned, U: UpdateMap<T>> List<T, N, U> {
         Ok(list)
     }
 
+    /// Create a new list by applying `f` to each element of this list, including pending updates.
+    ///
+    /// The mapped values are pushed directly into a builder for the new list.
+    pub fn map<R: Value>(&self, f: impl FnMut(&T) -> R) -> Result<List<R, N>, Error> {
+        List::try_from_iter(self.iter().map(f))
+    }
+
+    /// Create a new list by applying `f` to each element of this list, preserving the shape of
+    /// the tree.
+    ///
+    /// Subtrees shared within the tree are mapped only once and remain shared in the new list, so
+    /// `f` may be called fewer times than there are elements and must be pure. If there are
+    /// pending updates this falls back to `map`.
+    pub fn map_shared<R: Value>(&self, f: impl Fn(&T) -> R) -> Result<List<R, N>, Error> {
+        if self.has_pending_updates() {
+            return self.map(f);
+        }
+        let backing = &self.interface.backing;
+        let tree = backing.tree.map_leaves(&f)?;
+        Ok(List::from_parts(
+            tree,
+            List::<R, N>::depth(),
+            backing.length,
+        ))
+    }
+
+    /// Create a new list by applying the fallible `f` to each element of this list.
+    ///
+    /// Mapping stops at the first error returned by `f`, which is returned in the inner result.
+    /// The outer result contains any error from building the new list.
+    pub fn try_map<R: Value, E>(
+        &self,
+        f: impl FnMut(&T) -> Result<R, E>,
+    ) -> Result<Result<List<R, N>, E>, Error> {
+        match process_results(self.iter().map(f), List::try_from_iter) {
+            Ok(list) => list.map(Ok),
+            Err(e) => Ok(Err(e)),
+        }
+    }
+
+    /// Copy the list without sharing any part of its tree with `self`.
+    ///
+    /// Plain `clone` is cheap because the clone shares the tree with the original, and nodes are
+    /// only copied when they are modified. A deep clone copies every node up front, which is
+    /// useful before handing the list to another thread that will modify most of it.
+    pub fn deep_clone(&self) -> Self {
+        let mut cloned = self.clone();
+        cloned.interface.backing.tree = self.interface.backing.tree.deep_clone();
+        cloned
+    }
+
     pub fn to_vec(&self) -> Vec<T> {
         self.iter().cloned().collect()
     }
 
+    /// Convert the list into a `Vec`, moving values out of the tree where possible.
+    ///
+    /// Values in parts of the tree that are not shared with any other list are moved rather than
+    /// cloned. If there are pending updates every value is cloned, as for `to_vec`.
+    pub fn into_vec(self) -> Vec<T> {
+        if self.has_pending_updates() {
+            return self.to_vec();
+        }
+        let mut values = Vec::with_capacity(self.len());
+        Tree::into_values(self.interface.backing.tree, &mut values);
+        values
+    }
+
     pub fn iter(&self) -> InterfaceIter<T, U> {
         self.interface.iter()
     }
@@ -140,19 +315,286 @@ impl<T: Value, N: Unsigned, U: UpdateMap<T>> List<T, N, U> {
         self.interface.level_iter_from(index)
     }
 
+    /// Iterate the elements of the list as `Arc`s, which are shared with the list's tree.
+    ///
+    /// Elements with pending updates are cloned into new `Arc`s. Errors if `T` is packed, because
+    /// packed values are not stored in `Arc`s.
+    pub fn iter_arc(&self) -> Result<ArcInterfaceIter<T, U>, Error> {
+        let backing = &self.interface.backing;
+        Ok(ArcInterfaceIter {
+            tree_iter: ArcIter::from_index(
+                0,
+                &backing.tree,
+                backing.depth,
+                backing.length.as_usize(),
+            )?,
+            updates: &self.interface.updates,
+            index: 0,
+            length: self.len(),
+        })
+    }
+
     pub fn iter_cow(&mut self) -> InterfaceIterCow<T, U> {
         self.interface.iter_cow()
     }
 
+    /// Iterate mutable references to every element of the list.
+    ///
+    /// This copies every element into the pending updates up front, so it is only appropriate
+    /// when most elements will be modified. For sparse modifications prefer `iter_cow`.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.interface.iter_mut()
+    }
+
+    /// Map the tree hash root of each element to its index.
+    ///
+    /// If several elements have the same root the last index wins. Hashes cached in the leaves
+    /// of the tree are reused, while elements with pending updates are hashed from scratch.
+    pub fn index_by_tree_hash(&self) -> HashMap<Hash256, usize> {
+        let updates = &self.interface.updates;
+        let mut map = HashMap::with_capacity(self.len());
+        let mut index = 0;
+        self.interface
+            .backing
+            .tree
+            .for_each_element_hash(&mut |hash| {
+                let hash = updates.get(index).map_or(hash, TreeHash::tree_hash_root);
+                map.insert(hash, index);
+                index += 1;
+            });
+        // Pushed elements lie beyond the end of the tree.
+        for index in index..self.len() {
+            if let Some(value) = updates.get(index) {
+                map.insert(value.tree_hash_root(), index);
+            }
+        }
+        map
+    }
+
+    /// Iterate the elements at indices `start`, `start + step`, `start + 2 * step`, etc.
+    ///
+    /// Each element is looked up directly in O(log n) time, so elements between the yielded ones
+    /// are never visited. Pending updates are included.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `step` is 0.
+    pub fn stride(&self, start: usize, step: usize) -> impl Iterator<Item = &T> {
+        (start..self.len())
+            .step_by(step)
+            .filter_map(move |index| self.get(index))
+    }
+
+    /// Iterate the values of the packed leaves of the tree, in order, without copying them.
+    ///
+    /// Each slice holds one 32-byte chunk's worth of values, except the last which may be short.
+    /// Errors if `T` is not a packed type, or if there are pending updates.
+    pub fn chunk_leaves(&self) -> Result<impl Iterator<Item = &[T]>, Error> {
+        if opt_packing_depth::<T>().is_none() {
+            return Err(Error::ChunkLeavesNotPacked);
+        }
+        if self.has_pending_updates() {
+            return Err(Error::ChunkLeavesPendingUpdates);
+        }
+        let mut stack = vec![&*self.interface.backing.tree];
+        Ok(std::iter::from_fn(move || {
+            while let Some(node) = stack.pop() {
+                match node {
+                    Tree::Node { left, right, .. } => {
+                        stack.push(right);
+                        stack.push(left);
+                    }
+                    Tree::PackedLeaf(leaf) => return Some(leaf.as_slice()),
+                    Tree::Leaf(_) | Tree::Zero(_) => (),
+                }
+            }
+            None
+        }))
+    }
+
+    /// Check whether two lists contain the same elements in the same order.
+    ///
+    /// Unlike `PartialEq`, which compares the internal structure of the lists, this ignores tree
+    /// sharing and pending updates, so a list is always content-equal to its rebased or
+    /// flushed self.
+    pub fn eq_contents(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+
+    /// Compare the elements of two lists lexicographically, including pending updates.
+    ///
+    /// Like `eq_contents`, this ignores the internal structure of the lists, which is why it is
+    /// not exposed as an `Ord` or `PartialOrd` impl.
+    pub fn cmp_elements(&self, other: &Self) -> Ordering
+    where
+        T: Ord,
+    {
+        self.iter().cmp(other.iter())
+    }
+
+    /// Check whether the list is sorted according to `cmp`, stopping at the first pair of
+    /// elements that is out of order.
+    ///
+    /// Empty and single-element lists are always sorted.
+    pub fn is_sorted_by(&self, mut cmp: impl FnMut(&T, &T) -> Ordering) -> bool {
+        self.iter()
+            .tuple_windows()
+            .all(|(a, b)| cmp(a, b) != Ordering::Greater)
+    }
+
+    /// Iterate overlapping windows of `size` consecutive elements, like `slice::windows`.
+    ///
+    /// If the list is shorter than `size` no windows are yielded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    pub fn windows(&self, size: usize) -> impl Iterator<Item = Vec<&T>> {
+        assert!(size != 0, "window size must be non-zero");
+        let mut iter = self.iter();
+        let mut window = iter.by_ref().take(size - 1).collect::<VecDeque<_>>();
+        iter.map(move |value| {
+            window.push_back(value);
+            let result = window.iter().copied().collect();
+            window.pop_front();
+            result
+        })
+    }
+
+    /// Iterate pairs of elements from `self` and `other`, stopping at the end of the shorter list.
+    pub fn zip<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = (&'a T, &'a T)> {
+        self.iter().zip(other.iter())
+    }
+
+    /// Iterate pairs of elements from `self` and `other` along with their index, skipping
+    /// subtrees that are shared between the two lists.
+    ///
+    /// Subtrees are shared if they are pointer-equal or have equal cached hashes. Every index at
+    /// which the lists differ is yielded, but indices within a shared subtree are not, so this is
+    /// fast for lists that are mostly shared. Pairs are yielded in index order up to the end of
+    /// the shorter list. Errors if either list has pending updates.
+    pub fn zip_unshared<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> Result<impl Iterator<Item = (usize, &'a T, &'a T)>, Error> {
+        if self.has_pending_updates() || other.has_pending_updates() {
+            return Err(Error::ZipPendingUpdates);
+        }
+        let len = std::cmp::min(self.len(), other.len());
+        let backing = &self.interface.backing;
+        let mut ranges = vec![];
+        if !Arc::ptr_eq(&backing.tree, &other.interface.backing.tree) {
+            backing.tree.unshared_ranges(
+                &other.interface.backing.tree,
+                0,
+                backing.depth,
+                backing.packing_depth,
+                &mut ranges,
+            );
+        }
+        Ok(ranges
+            .into_iter()
+            .map(move |range| range.start.min(len)..range.end.min(len))
+            .flat_map(move |range| {
+                let start = range.start;
+                range
+                    .zip(self.interface.iter_from(start))
+                    .zip(other.interface.iter_from(start))
+                    .map(|((i, x), y)| (i, x, y))
+            }))
+    }
+
+    /// Iterate the indices at which `self` and `other` differ, in index order.
+    ///
+    /// Indices past the end of the shorter list are always considered different. Shared subtrees
+    /// are skipped as in `zip_unshared`. Errors if either list has pending updates.
+    pub fn diff_indices<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> Result<impl Iterator<Item = usize> + 'a, Error> {
+        if self.has_pending_updates() || other.has_pending_updates() {
+            return Err(Error::DiffPendingUpdates);
+        }
+        let min_len = std::cmp::min(self.len(), other.len());
+        let max_len = std::cmp::max(self.len(), other.len());
+        Ok(self
+            .zip_unshared(other)?
+            .filter(|(_, x, y)| x != y)
+            .map(|(i, _, _)| i)
+            .chain(min_len..max_len))
+    }
+
     // Wrap trait methods so we present a Vec-like interface without having to import anything.
     pub fn get(&self, index: usize) -> Option<&T> {
         self.interface.get(index)
     }
 
+    /// Get the element at `index` as an `Arc`, which is shared with the list's tree.
+    ///
+    /// An element with a pending update is cloned into a new `Arc`. Return `None` if `index` is
+    /// out of bounds, or if `T` is packed.
+    pub fn get_arc(&self, index: usize) -> Option<Arc<T>> {
+        if index >= self.len() {
+            return None;
+        }
+        if let Some(value) = self.interface.updates.get(index) {
+            return Some(Arc::new(value.clone()));
+        }
+        let backing = &self.interface.backing;
+        ArcIter::from_index(
+            index,
+            &backing.tree,
+            backing.depth,
+            backing.length.as_usize(),
+        )
+        .ok()?
+        .next()
+        .cloned()
+    }
+
+    /// Get the element at `index`, erroring if `index` is out of bounds.
+    pub fn try_get(&self, index: usize) -> Result<&T, Error> {
+        self.get(index).ok_or_else(|| Error::OutOfBounds {
+            index,
+            len: self.len(),
+        })
+    }
+
     pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
         self.interface.get_mut(index)
     }
 
+    /// Get a mutable reference to the element at `index`, pushing `default()` first if `index` is
+    /// the length of the list.
+    ///
+    /// Errors if `index` is greater than the length of the list, or if the list is full.
+    pub fn get_or_push(
+        &mut self,
+        index: usize,
+        default: impl FnOnce() -> T,
+    ) -> Result<&mut T, Error> {
+        let len = self.len();
+        if index == len {
+            self.push(default())?;
+        }
+        self.get_mut(index).ok_or(Error::OutOfBounds { index, len })
+    }
+
+    /// Apply `f` to the element at each of `indices`, recording the results as pending updates.
+    ///
+    /// The backing tree is descended once for all indices without pending updates, rather than
+    /// once per index.
+    ///
+    /// Indices are visited in ascending order and at most once each, even if repeated. Errors
+    /// without modifying the list if any index is out of bounds.
+    pub fn update_each(
+        &mut self,
+        indices: &[usize],
+        f: impl FnMut(usize, &mut T),
+    ) -> Result<(), Error> {
+        self.interface.update_each(indices, f)
+    }
+
     pub fn get_cow(&mut self, index: usize) -> Option<Cow<T>> {
         self.interface.get_cow(index)
     }
@@ -161,10 +603,21 @@ impl<T: Value, N: Unsigned, U: UpdateMap<T>> List<T, N, U> {
         self.interface.push(value)
     }
 
+    /// Push a value onto the end of the list, returning the remaining capacity.
+    pub fn try_push(&mut self, value: T) -> Result<usize, Error> {
+        self.push(value)?;
+        Ok(self.remaining_capacity())
+    }
+
     pub fn len(&self) -> usize {
         self.interface.len()
     }
 
+    /// Number of elements that can be pushed before the list is full.
+    pub fn remaining_capacity(&self) -> usize {
+        N::to_usize().saturating_sub(self.len())
+    }
+
     pub fn is_empty(&self) -> bool {
         self.interface.is_empty()
     }
@@ -173,10 +626,114 @@ impl<T: Value, N: Unsigned, U: UpdateMap<T>> List<T, N, U> {
         self.interface.has_pending_updates()
     }
 
+    /// Iterate the indices with pending updates in index order, without cloning any values.
+    pub fn pending_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.interface.pending_indices()
+    }
+
+    /// Number of indices with pending updates.
+    pub fn pending_len(&self) -> usize {
+        self.interface.pending_len()
+    }
+
+    /// Discard the pending update for `index`, returning the discarded value if present.
+    ///
+    /// Pushed values can only be discarded from the end of the list.
+    pub fn discard_pending(&mut self, index: usize) -> Result<Option<T>, Error> {
+        self.interface.discard_pending(index)
+    }
+
+    /// Retain only the pending updates for which `f` returns `true`.
+    ///
+    /// Pushed values can only be discarded from the end of the list.
+    pub fn retain_pending(&mut self, f: impl FnMut(usize, &T) -> bool) -> Result<(), Error> {
+        self.interface.retain_pending(f)
+    }
+
+    /// Count the nodes whose hashes will be computed by the next tree hash.
+    ///
+    /// Pending updates are not included, call `apply_updates` first.
+    pub fn dirty_node_count(&self) -> usize {
+        self.interface.backing.tree.dirty_node_count()
+    }
+
+    /// Render the list's tree as an indented outline, see `Tree::render_ascii`.
+    ///
+    /// Pending updates are not included.
+    #[cfg(feature = "debug")]
+    pub fn render_ascii(&self, max_depth: usize) -> String {
+        self.interface.backing.tree.render_ascii(max_depth)
+    }
+
+    /// Count the distinct nodes of each kind in the list's tree.
+    ///
+    /// Pending updates are not included.
+    pub fn structure_stats(&self) -> TreeStats {
+        self.interface.backing.tree.structure_stats()
+    }
+
+    /// Panic if the cached length of the list doesn't match the length of its tree.
+    ///
+    /// Pending updates are not checked. This is compiled out in release builds.
+    pub fn debug_assert_len_consistent(&self) {
+        debug_assert_eq!(
+            self.interface.backing.len().as_usize(),
+            self.interface.backing.tree.compute_len(),
+            "cached length doesn't match tree"
+        );
+    }
+
+    /// Reserve capacity in the update map for `additional` more updates.
+    ///
+    /// See `UpdateMap::reserve`.
+    pub fn reserve_updates(&mut self, additional: usize) {
+        self.interface.updates.reserve(additional);
+    }
+
     pub fn apply_updates(&mut self) -> Result<(), Error> {
         self.interface.apply_updates()
     }
 
+    /// Apply the pending updates, updating large subtrees in parallel.
+    ///
+    /// This produces the same list as `apply_updates` and is faster when there are many updates
+    /// scattered across the list.
+    pub fn apply_updates_par(&mut self) -> Result<(), Error>
+    where
+        T: Send + Sync,
+        U: Sync,
+    {
+        self.interface.apply_updates_par()
+    }
+
+    /// Fold over the elements of the list, skipping subtrees whose elements all hash to zero.
+    ///
+    /// Each element is passed to `leaf` in index order, except for runs of elements within an
+    /// internal node whose cached hash is the zero hash. For these `zero` is called with the
+    /// number of elements in the run, so sparse lists of packed values can be aggregated in time
+    /// proportional to their populated part. Only subtrees hashed by a previous call to
+    /// `tree_hash_root` can be skipped. Errors if the list has pending updates.
+    pub fn fold_subtrees<A>(
+        &self,
+        init: A,
+        mut leaf: impl FnMut(A, &T) -> A,
+        mut zero: impl FnMut(A, usize) -> A,
+    ) -> Result<A, Error> {
+        if self.has_pending_updates() {
+            return Err(Error::FoldPendingUpdates);
+        }
+        let backing = &self.interface.backing;
+        Ok(backing.tree.fold_subtrees(
+            init,
+            0,
+            backing.depth,
+            backing.packing_depth,
+            self.len(),
+            &mut leaf,
+            &mut zero,
+        ))
+    }
+
     pub fn bulk_update(&mut self, updates: U) -> Result<(), Error> {
         self.interface.bulk_update(updates)
     }
@@ -238,6 +795,16 @@ impl<T: Value, N: Unsigned, U: UpdateMap<T>> List<T, N, U> {
     }
 }
 
+impl<T: Value, N: Unsigned> PartialEq for ListInner<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        // Lists sharing the same tree are equal without needing to compare their contents.
+        self.length == other.length
+            && self.depth == other.depth
+            && self.packing_depth == other.packing_depth
+            && (Arc::ptr_eq(&self.tree, &other.tree) || self.tree == other.tree)
+    }
+}
+
 impl<T: Value, N: Unsigned> ImmList<T> for ListInner<T, N> {
     fn get(&self, index: usize) -> Option<&T> {
         if index < self.len().as_usize() {
@@ -248,6 +815,19 @@ impl<T: Value, N: Unsigned> ImmList<T> for ListInner<T, N> {
         }
     }
 
+    fn get_many(&self, indices: &[usize]) -> Vec<(usize, &T)> {
+        let end = indices.partition_point(|&index| index < self.len().as_usize());
+        let mut values = Vec::with_capacity(end);
+        self.tree.get_many_recursive(
+            &indices[..end],
+            0,
+            self.depth,
+            self.packing_depth,
+            &mut values,
+        );
+        values
+    }
+
     fn len(&self) -> Length {
         self.length
     }
@@ -284,7 +864,7 @@ where
 
         self.tree = self.tree.with_updated_leaf(index, value, self.depth)?;
         if index == self.length.as_usize() {
-            *self.length.as_mut() += 1;
+            self.length = Length(index.checked_add(1).ok_or(Error::InvalidListUpdate)?);
         }
         Ok(())
     }
@@ -302,12 +882,31 @@ where
             // Nothing to do.
             return Ok(());
         }
-        self.length = updated_length(self.length, &updates);
+        self.length = checked_updated_length(self.length, &updates)?;
         self.tree =
             self.tree
                 .with_updated_leaves(&updates, 0, self.depth, hash_updates.as_ref())?;
         Ok(())
     }
+
+    fn update_par<U: UpdateMap<T> + Sync>(&mut self, updates: U) -> Result<(), Error>
+    where
+        T: Send + Sync,
+    {
+        if let Some(max_index) = updates.max_index() {
+            if max_index >= N::to_usize() {
+                return Err(Error::InvalidListUpdate);
+            }
+        } else {
+            // Nothing to do.
+            return Ok(());
+        }
+        self.length = checked_updated_length(self.length, &updates)?;
+        self.tree = self
+            .tree
+            .with_updated_leaves_par(&updates, 0, self.depth, None)?;
+        Ok(())
+    }
 }
 
 impl<T: Value, N: Unsigned, U: UpdateMap<T>> List<T, N, U> {
@@ -318,21 +917,97 @@ impl<T: Value, N: Unsigned, U: UpdateMap<T>> List<T, N, U> {
     }
 
     pub fn rebase_on(&mut self, base: &Self) -> Result<(), Error> {
-        match Tree::rebase_on(
+        self.rebase_on_with_stats(base)?;
+        Ok(())
+    }
+
+    /// Rebase `self` on `base`, returning statistics about how much of the tree was shared.
+    pub fn rebase_on_with_stats(&mut self, base: &Self) -> Result<RebaseStats, Error> {
+        self.rebase_on_tree_with_stats(&base.interface.backing.tree, base.interface.backing.length)
+    }
+
+    /// Rebase `self` on a list with a (possibly) different capacity `N2`.
+    ///
+    /// The base list must have the same tree depth as `self`, which is the case for lists whose
+    /// capacities round up to the same power of two. An error is returned if the depths differ.
+    pub fn rebase_on_list<N2: Unsigned, U2: UpdateMap<T>>(
+        &mut self,
+        base: &List<T, N2, U2>,
+    ) -> Result<(), Error> {
+        self.rebase_on_tree(&base.interface.backing.tree, base.len())
+    }
+
+    /// Rebase `self` on a raw `base_tree` containing `base_len` elements, checking its depth.
+    pub(crate) fn rebase_on_tree(
+        &mut self,
+        base_tree: &Arc<Tree<T>>,
+        base_len: usize,
+    ) -> Result<(), Error> {
+        let depth = self.interface.backing.depth;
+        let base_depth = base_tree.compute_depth();
+        if base_depth != depth {
+            return Err(Error::RebaseDepthMismatch { depth, base_depth });
+        }
+        self.rebase_on_tree_with_stats(base_tree, Length(base_len))?;
+        Ok(())
+    }
+
+    fn rebase_on_tree_with_stats(
+        &mut self,
+        base_tree: &Arc<Tree<T>>,
+        base_length: Length,
+    ) -> Result<RebaseStats, Error> {
+        let mut stats = RebaseStats::default();
+        match Tree::rebase_on_with_stats(
             &self.interface.backing.tree,
-            &base.interface.backing.tree,
-            Some((self.interface.backing.length, base.interface.backing.length)),
+            base_tree,
+            Some((self.interface.backing.length, base_length)),
             self.interface.backing.depth + self.interface.backing.packing_depth,
+            &mut stats,
         )? {
             RebaseAction::EqualReplace(replacement) => {
                 self.interface.backing.tree = replacement.clone();
+                stats.nodes_replaced += 1;
             }
             RebaseAction::NotEqualReplace(replacement) => {
                 self.interface.backing.tree = replacement;
             }
             _ => (),
         }
-        Ok(())
+        Ok(stats)
+    }
+
+    /// Take a snapshot of the current state of the list, including its pending updates.
+    ///
+    /// The pending updates are applied to the snapshot but remain pending in `self`.
+    pub fn snapshot(&self) -> Result<Snapshot<T, N, U>, Error> {
+        let mut list = self.clone();
+        list.apply_updates()?;
+        Ok(Snapshot { list })
+    }
+
+    /// Revert the list to the state saved in `snapshot`, discarding any pending updates.
+    pub fn restore(&mut self, snapshot: &Snapshot<T, N, U>) {
+        debug_assert!(!snapshot.list.has_pending_updates());
+        *self = snapshot.list.clone();
+    }
+}
+
+/// Hash of the elements of the list, including pending updates.
+///
+/// The internal structure of the tree is not hashed, so lists with equal elements have equal
+/// hashes even if they are not equal according to `PartialEq`.
+impl<T, N, U> Hash for List<T, N, U>
+where
+    T: Value + Hash,
+    N: Unsigned,
+    U: UpdateMap<T>,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for item in self.iter() {
+            item.hash(state);
+        }
     }
 }
 
@@ -342,7 +1017,7 @@ impl<T: Value, N: Unsigned> Default for List<T, N> {
     }
 }
 
-impl<T: Value + Send + Sync, N: Unsigned> TreeHash for List<T, N> {
+impl<T: Value + Send + Sync, N: Unsigned, U: UpdateMap<T>> TreeHash for List<T, N, U> {
     fn tree_hash_type() -> tree_hash::TreeHashType {
         tree_hash::TreeHashType::List
     }
@@ -364,6 +1039,94 @@ impl<T: Value + Send + Sync, N: Unsigned> TreeHash for List<T, N> {
     }
 }
 
+impl<T: Value + Send + Sync, N: Unsigned, U: UpdateMap<T>> List<T, N, U> {
+    /// Compute the tree hash root of the list, erroring if there are pending updates.
+    pub fn tree_hash_root_cached(&self) -> Result<Hash256, Error> {
+        if self.has_pending_updates() {
+            return Err(Error::TreeHashPendingUpdates);
+        }
+        let root = self.interface.backing.tree.tree_hash();
+        Ok(tree_hash::mix_in_length(&root, self.len()))
+    }
+
+    /// Check that the tree hash root of the list is `expected`.
+    ///
+    /// Errors if the roots differ, or if there are pending updates.
+    pub fn verify_root(&self, expected: Hash256) -> Result<(), Error> {
+        let actual = self.tree_hash_root_cached()?;
+        if actual != expected {
+            return Err(Error::RootMismatch { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Get the hash of the node at `index` on `level` of the tree, where level 0 contains the
+    /// leaves and level `depth` contains only the root.
+    ///
+    /// For packed types the leaves are the 32-byte chunks rather than individual elements.
+    /// The length mix-in is not part of the tree, so the root on level `depth` is the hash
+    /// before mixing in the length.
+    /// Return `None` if the position is out of range, or if there are pending updates.
+    pub fn node_hash(&self, index: usize, level: usize) -> Option<Hash256> {
+        if self.has_pending_updates() {
+            return None;
+        }
+        let backing = &self.interface.backing;
+        backing.tree.subtree_hash(index, level, backing.depth)
+    }
+
+    /// Apply pending updates and compute the tree hash root inside the given thread pool.
+    ///
+    /// All parallel hashing runs on `pool`, which allows the caller to bound the parallelism
+    /// used when hashing many lists concurrently. Computed hashes are cached in the tree as usual,
+    /// so they are shared with clones of this list and reused by later calls on any thread.
+    #[cfg(feature = "rayon")]
+    pub fn tree_hash_root_in(&mut self, pool: &rayon::ThreadPool) -> Result<Hash256, Error>
+    where
+        U: Sync,
+    {
+        self.apply_updates()?;
+        let this = &*self;
+        pool.install(|| this.tree_hash_root_cached())
+    }
+
+    /// Compute the tree hash root of the list on the current thread, without using rayon.
+    ///
+    /// Errors if there are pending updates.
+    pub fn tree_hash_root_sequential(&self) -> Result<Hash256, Error> {
+        if self.has_pending_updates() {
+            return Err(Error::TreeHashPendingUpdates);
+        }
+        let root = self.interface.backing.tree.tree_hash_sequential();
+        Ok(tree_hash::mix_in_length(&root, self.len()))
+    }
+
+    /// Compute the tree hash root of the list without caching any newly computed hashes.
+    ///
+    /// See `Tree::tree_hash_readonly`. Errors if there are pending updates.
+    pub fn tree_hash_root_readonly(&self) -> Result<Hash256, Error> {
+        if self.has_pending_updates() {
+            return Err(Error::TreeHashPendingUpdates);
+        }
+        let root = self.interface.backing.tree.tree_hash_readonly();
+        Ok(tree_hash::mix_in_length(&root, self.len()))
+    }
+
+    /// Compute the tree hash root of the list including any pending updates.
+    ///
+    /// The updates are applied to a copy of the list, so `self` is left unmodified and the hashes
+    /// of the updated nodes are not cached. Prefer `apply_updates` followed by `tree_hash_root`
+    /// when a mutable reference is available.
+    pub fn tree_hash_root_flushing(&self) -> Result<Hash256, Error> {
+        if !self.has_pending_updates() {
+            return self.tree_hash_root_cached();
+        }
+        let mut flushed = self.clone();
+        flushed.apply_updates()?;
+        flushed.tree_hash_root_cached()
+    }
+}
+
 impl<'a, T: Value, N: Unsigned, U: UpdateMap<T>> IntoIterator for &'a List<T, N, U> {
     type Item = &'a T;
     type IntoIter = InterfaceIter<'a, T, U>;
@@ -404,7 +1167,7 @@ where
 }
 
 // FIXME: duplicated from `ssz::encode::impl_for_vec`
-impl<T: Value, N: Unsigned> Encode for List<T, N> {
+impl<T: Value, N: Unsigned, U: UpdateMap<T>> Encode for List<T, N, U> {
     fn is_ssz_fixed_len() -> bool {
         false
     }
@@ -423,8 +1186,12 @@ impl<T: Value, N: Unsigned> Encode for List<T, N> {
         if <T as Encode>::is_ssz_fixed_len() {
             buf.reserve(<T as Encode>::ssz_fixed_len() * self.len());
 
-            for item in self {
-                item.ssz_append(buf);
+            if !self.has_pending_updates() && ssz_is_packed_encoding::<T>() {
+                self.interface.backing.tree.ssz_append_packed(buf);
+            } else {
+                for item in self {
+                    item.ssz_append(buf);
+                }
             }
         } else {
             let mut encoder = SszEncoder::container(buf, self.len() * BYTES_PER_LENGTH_OFFSET);
@@ -438,6 +1205,21 @@ impl<T: Value, N: Unsigned> Encode for List<T, N> {
     }
 }
 
+/// Read from `reader` until `buf` is full or the reader is exhausted, returning the number of
+/// bytes read.
+fn read_up_to(reader: &mut impl Read, buf: &mut [u8]) -> Result<usize, Error> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(Error::SszReaderIo { kind: e.kind() }),
+        }
+    }
+    Ok(filled)
+}
+
 impl<T, N> TryFromIter<T> for List<T, N>
 where
     T: Value,
@@ -453,18 +1235,58 @@ where
     }
 }
 
-impl<T, N> Decode for List<T, N>
-where
-    T: Value,
-    N: Unsigned,
+impl<T: Value, N: Unsigned, U: UpdateMap<T>> TryFrom<Vec<T>> for List<T, N, U> {
+    type Error = Error;
+
+    fn try_from(vec: Vec<T>) -> Result<Self, Error> {
+        if vec.len() > N::to_usize() {
+            return Err(Error::ListFull { len: N::to_usize() });
+        }
+        Self::try_from_iter(vec)
+    }
+}
+
+impl<T: Value, N: Unsigned, U: UpdateMap<T>> TryFrom<&[T]> for List<T, N, U> {
+    type Error = Error;
+
+    fn try_from(slice: &[T]) -> Result<Self, Error> {
+        if slice.len() > N::to_usize() {
+            return Err(Error::ListFull { len: N::to_usize() });
+        }
+        Self::try_from_iter(slice.iter().cloned())
+    }
+}
+
+#[cfg(feature = "ssz_types")]
+impl<T: Value, N: Unsigned, U: UpdateMap<T>> From<ssz_types::VariableList<T, N>> for List<T, N, U> {
+    fn from(list: ssz_types::VariableList<T, N>) -> Self {
+        Self::try_from_iter(Into::<Vec<T>>::into(list))
+            .expect("VariableList length never exceeds N")
+    }
+}
+
+#[cfg(feature = "ssz_types")]
+impl<T: Value, N: Unsigned, U: UpdateMap<T>> TryFrom<List<T, N, U>>
+    for ssz_types::VariableList<T, N>
 {
-    fn is_ssz_fixed_len() -> bool {
-        false
+    type Error = ssz_types::Error;
+
+    fn try_from(list: List<T, N, U>) -> Result<Self, Self::Error> {
+        Self::new(list.to_vec())
     }
+}
 
-    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, ssz::DecodeError> {
-        let max_len = N::to_usize();
+impl<T: Value, N: Unsigned> List<T, N> {
+    /// Decode a list from SSZ bytes, erroring if it contains more than `max_items` items.
+    ///
+    /// This allows a tighter limit than `N` to be imposed on untrusted input. The number of items
+    /// is checked before any of them are decoded, so oversized input is rejected cheaply.
+    pub fn from_ssz_bytes_limited(bytes: &[u8], max_items: usize) -> Result<Self, Error> {
+        Self::from_ssz_bytes_with_max_len(bytes, std::cmp::min(N::to_usize(), max_items))
+            .map_err(|error| Error::SszDecode { error })
+    }
 
+    fn from_ssz_bytes_with_max_len(bytes: &[u8], max_len: usize) -> Result<Self, ssz::DecodeError> {
         if bytes.is_empty() {
             Ok(List::empty())
         } else if <T as Decode>::is_ssz_fixed_len() {
@@ -495,3 +1317,17 @@ where
         }
     }
 }
+
+impl<T, N> Decode for List<T, N>
+where
+    T: Value,
+    N: Unsigned,
+{
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, ssz::DecodeError> {
+        Self::from_ssz_bytes_with_max_len(bytes, N::to_usize())
+    }
+}
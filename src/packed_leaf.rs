@@ -29,13 +29,21 @@ where
 impl<T: TreeHash + Clone> PackedLeaf<T> {
     pub fn tree_hash(&self) -> Hash256 {
         let read_lock = self.hash.read();
-        let mut hash = *read_lock;
+        let hash = *read_lock;
         drop(read_lock);
 
         if !hash.is_zero() {
             return hash;
         }
 
+        let hash = self.compute_hash();
+        *self.hash.write() = hash;
+        hash
+    }
+
+    /// Compute the hash of this leaf without reading or writing the cached hash.
+    pub fn compute_hash(&self) -> Hash256 {
+        let mut hash = Hash256::ZERO;
         let hash_bytes = hash.as_mut_slice();
 
         let value_len = BYTES_PER_CHUNK / T::tree_hash_packing_factor();
@@ -44,10 +52,19 @@ impl<T: TreeHash + Clone> PackedLeaf<T> {
                 .copy_from_slice(&value.tree_hash_packed_encoding());
         }
 
-        *self.hash.write() = hash;
         hash
     }
 
+    /// The values stored in this leaf.
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Iterate the values stored in this leaf.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.values.iter()
+    }
+
     pub fn empty() -> Self {
         PackedLeaf {
             hash: RwLock::new(Hash256::ZERO),
@@ -65,6 +82,22 @@ impl<T: TreeHash + Clone> PackedLeaf<T> {
         }
     }
 
+    /// Create a leaf containing `values`.
+    ///
+    /// Errors if there are more values than fit in a single leaf.
+    pub fn from_slice(values: &[T]) -> Result<Self, Error> {
+        let packing_factor = T::tree_hash_packing_factor();
+        if values.len() > packing_factor {
+            return Err(Error::PackedLeafFull { len: values.len() });
+        }
+        let mut leaf_values = Vec::with_capacity(packing_factor);
+        leaf_values.extend_from_slice(values);
+        Ok(PackedLeaf {
+            hash: RwLock::new(Hash256::ZERO),
+            values: leaf_values,
+        })
+    }
+
     pub fn repeat(value: T, n: usize) -> Self {
         assert!(n <= T::tree_hash_packing_factor());
         PackedLeaf {
@@ -98,6 +131,14 @@ impl<T: TreeHash + Clone> PackedLeaf<T> {
         let start = prefix;
         let end = prefix + packing_factor;
         updates.for_each_range(start, end, |index, value| {
+            // Updates must extend the leaf contiguously, without leaving holes.
+            let next_index = prefix + updated.values.len();
+            if index > next_index {
+                return ControlFlow::Continue(Err(Error::PackedLeafUpdateGap {
+                    index,
+                    next_index,
+                }));
+            }
             ControlFlow::Continue(updated.insert_mut(index % packing_factor, value.clone()))
         })?;
         Ok(updated)
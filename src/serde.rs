@@ -4,6 +4,9 @@ use serde::Deserialize;
 use std::marker::PhantomData;
 use typenum::Unsigned;
 
+pub mod bytes;
+pub mod hex;
+
 pub struct ListVisitor<T, N, U> {
     _phantom: PhantomData<(T, N, U)>,
 }
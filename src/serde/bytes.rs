@@ -0,0 +1,67 @@
+//! Compact serde representation for lists and vectors of bytes.
+//!
+//! Use with `#[serde(with = "milhouse::serde::bytes")]` on a `List<u8, N>` or `Vector<u8, N>`
+//! field. Formats which are not human-readable (e.g. bincode) encode the field as a single byte
+//! string, while human-readable formats use the usual sequence representation.
+use serde::de::{Error as _, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use ssz::TryFromIter;
+use std::fmt::{self, Debug};
+
+pub fn serialize<'a, C, S>(value: &'a C, serializer: S) -> Result<S::Ok, S::Error>
+where
+    C: Serialize,
+    &'a C: IntoIterator<Item = &'a u8>,
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        value.serialize(serializer)
+    } else {
+        let bytes = value.into_iter().copied().collect::<Vec<u8>>();
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
+pub fn deserialize<'de, C, D>(deserializer: D) -> Result<C, D::Error>
+where
+    C: Deserialize<'de> + TryFromIter<u8>,
+    C::Error: Debug,
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        C::deserialize(deserializer)
+    } else {
+        let bytes = deserializer.deserialize_byte_buf(BytesVisitor)?;
+        C::try_from_iter(bytes)
+            .map_err(|e| D::Error::custom(format!("Error deserializing bytes: {:?}", e)))
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a byte string")
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,34 @@
+//! Serde representation of lists and vectors of bytes as `0x`-prefixed hex strings.
+//!
+//! Use with `#[serde(with = "milhouse::serde::hex")]` on a `List<u8, N>` or `Vector<u8, N>`
+//! field, as is conventional for Ethereum JSON APIs.
+use alloy_primitives::hex;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serializer};
+use ssz::TryFromIter;
+use std::fmt::Debug;
+
+pub fn serialize<'a, C, S>(value: &'a C, serializer: S) -> Result<S::Ok, S::Error>
+where
+    &'a C: IntoIterator<Item = &'a u8>,
+    S: Serializer,
+{
+    let bytes = value.into_iter().copied().collect::<Vec<u8>>();
+    serializer.serialize_str(&hex::encode_prefixed(bytes))
+}
+
+pub fn deserialize<'de, C, D>(deserializer: D) -> Result<C, D::Error>
+where
+    C: TryFromIter<u8>,
+    C::Error: Debug,
+    D: Deserializer<'de>,
+{
+    let string = String::deserialize(deserializer)?;
+    let hex_str = string
+        .strip_prefix("0x")
+        .ok_or_else(|| D::Error::custom("hex string must have 0x prefix"))?;
+    let bytes = hex::decode(hex_str)
+        .map_err(|e| D::Error::custom(format!("Invalid hex string: {:?}", e)))?;
+    C::try_from_iter(bytes)
+        .map_err(|e| D::Error::custom(format!("Error deserializing hex bytes: {:?}", e)))
+}
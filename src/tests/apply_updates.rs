@@ -0,0 +1,194 @@
+use super::proptest::{arb_hash256, arb_index};
+use crate::interface::ImmList;
+use crate::{Arc, Error, List, Tree, Value};
+use proptest::prelude::*;
+use std::fmt::Debug;
+use tree_hash::{Hash256, TreeHash};
+use typenum::{Unsigned, U1048576, U8192};
+
+/// Check that parallel and sequential update application produce identical lists.
+fn check_par_matches_seq<T, N>(init: Vec<T>, updates: Vec<(usize, T)>, pushes: Vec<T>)
+where
+    T: Value + Send + Sync + Debug,
+    N: Unsigned + Debug,
+{
+    let mut seq = List::<T, N>::new(init).unwrap();
+    for (i, value) in updates {
+        if let Some(elem) = seq.get_mut(i) {
+            *elem = value;
+        }
+    }
+    for value in pushes {
+        seq.push(value).unwrap();
+    }
+    let mut par = seq.clone();
+
+    seq.apply_updates().unwrap();
+    par.apply_updates_par().unwrap();
+
+    assert_eq!(seq, par);
+    assert_eq!(seq.tree_hash_root(), par.tree_hash_root());
+}
+
+proptest! {
+    #[test]
+    fn par_updates_u64(
+        updates in proptest::collection::vec((arb_index(4096), any::<u64>()), 0..2048),
+        pushes in proptest::collection::vec(any::<u64>(), 0..2048),
+    ) {
+        check_par_matches_seq::<u64, U8192>((0..4096).collect(), updates, pushes);
+    }
+
+    #[test]
+    fn par_updates_hash256(
+        updates in proptest::collection::vec((arb_index(1024), arb_hash256()), 0..1024),
+        pushes in proptest::collection::vec(arb_hash256(), 0..1024),
+    ) {
+        let init = (0..1024u64)
+            .map(|i| Hash256::right_padding_from(&i.to_le_bytes()))
+            .collect();
+        check_par_matches_seq::<Hash256, U8192>(init, updates, pushes);
+    }
+}
+
+/// Assert that every subtree of `new` containing none of the `updated` indices is the same `Arc`
+/// as the corresponding subtree of `old`.
+fn assert_untouched_shared<T: Value>(
+    new: &Arc<Tree<T>>,
+    old: &Arc<Tree<T>>,
+    start: usize,
+    depth: usize,
+    packing_depth: usize,
+    updated: &[usize],
+) {
+    let end = start + (1 << (depth + packing_depth));
+    if !updated.iter().any(|i| (start..end).contains(i)) {
+        assert!(Arc::ptr_eq(new, old), "subtree at {start} was rebuilt");
+        return;
+    }
+    if let (
+        Tree::Node { left, right, .. },
+        Tree::Node {
+            left: old_left,
+            right: old_right,
+            ..
+        },
+    ) = (&**new, &**old)
+    {
+        let mid = start + (1 << (depth - 1 + packing_depth));
+        assert_untouched_shared(left, old_left, start, depth - 1, packing_depth, updated);
+        assert_untouched_shared(right, old_right, mid, depth - 1, packing_depth, updated);
+    }
+}
+
+#[test]
+fn par_updates_split_below_capacity() {
+    // The right half of the root is empty, so the parallel path has to descend to find a split.
+    let mut seq = List::<u64, U1048576>::try_from_iter(0..10_000).unwrap();
+    let original = seq.interface.backing.tree.clone();
+    let updated = (0..5_000).step_by(10).collect::<Vec<_>>();
+    for &i in &updated {
+        *seq.get_mut(i).unwrap() += 1;
+    }
+    let mut par = seq.clone();
+
+    par.apply_updates_par().unwrap();
+    seq.apply_updates().unwrap();
+    assert_eq!(seq, par);
+    assert_eq!(seq.tree_hash_root(), par.tree_hash_root());
+
+    let backing = &par.interface.backing;
+    assert_untouched_shared(
+        &backing.tree,
+        &original,
+        0,
+        backing.depth,
+        backing.packing_depth,
+        &updated,
+    );
+}
+
+#[test]
+fn update_each() {
+    let mut list = List::<u64, U8192>::try_from_iter(0..100).unwrap();
+    let mut visited = vec![];
+    list.update_each(&[50, 3, 99, 3], |i, x| {
+        visited.push(i);
+        *x *= 2;
+    })
+    .unwrap();
+    assert_eq!(visited, vec![3, 50, 99]);
+    assert_eq!(list.pending_len(), 3);
+
+    list.apply_updates().unwrap();
+    let expected = (0..100)
+        .map(|i| if [3, 50, 99].contains(&i) { i * 2 } else { i })
+        .collect::<Vec<_>>();
+    assert_eq!(list.to_vec(), expected);
+}
+
+#[test]
+fn get_many_matches_get() {
+    fn check<T: Value + Debug>(list: &List<T, U8192>, indices: &[usize]) {
+        let backing = &list.interface.backing;
+        let expected = indices
+            .iter()
+            .filter_map(|&i| Some((i, backing.get(i)?)))
+            .collect::<Vec<_>>();
+        assert_eq!(backing.get_many(indices), expected);
+    }
+    let indices = [0, 1, 2, 3, 4, 7, 8, 500, 998, 999, 1000, 5000];
+
+    let list = List::<u64, U8192>::try_from_iter(0..1000).unwrap();
+    check(&list, &indices);
+    check(&list, &[]);
+    let list = List::<Hash256, U8192>::try_from_iter(
+        (0..1000).map(|i| Hash256::right_padding_from(&(i as u64).to_le_bytes())),
+    )
+    .unwrap();
+    check(&list, &indices);
+    check(&List::<u8, U8192>::repeat(7, 999).unwrap(), &indices);
+}
+
+#[test]
+fn update_each_mixed_pending() {
+    let mut list = List::<u64, U8192>::try_from_iter(0..100).unwrap();
+    *list.get_mut(10).unwrap() = 1000;
+    list.push(100).unwrap();
+    list.update_each(&[100, 10, 11, 0], |_, x| *x += 1).unwrap();
+
+    list.apply_updates().unwrap();
+    let expected = (0..=100)
+        .map(|i| match i {
+            10 => 1001,
+            0 | 11 | 100 => i + 1,
+            _ => i,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(list.to_vec(), expected);
+}
+
+#[test]
+fn update_each_out_of_bounds() {
+    let mut list = List::<u64, U8192>::try_from_iter(0..100).unwrap();
+    assert_eq!(
+        list.update_each(&[1, 100], |_, x| *x = 0),
+        Err(Error::OutOfBounds {
+            index: 100,
+            len: 100
+        })
+    );
+    assert!(!list.has_pending_updates());
+}
+
+#[test]
+fn huge_update_index() {
+    type L = List<u64, typenum::U8, std::collections::BTreeMap<usize, u64>>;
+    for index in [usize::MAX - 1, usize::MAX] {
+        let mut list = L::new(vec![1, 2, 3]).unwrap();
+        list.bulk_update(std::collections::BTreeMap::from([(index, 0)]))
+            .unwrap();
+        assert_eq!(list.len(), index.saturating_add(1));
+        assert_eq!(list.apply_updates(), Err(Error::InvalidListUpdate));
+    }
+}
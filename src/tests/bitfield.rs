@@ -0,0 +1,59 @@
+use crate::{Bitfield, Error};
+use ssz_types::BitList;
+use tree_hash::TreeHash;
+use typenum::{Unsigned, U1, U1024, U2048, U256, U257, U8};
+
+fn check_against_bitlist<N: Unsigned + Clone>(pattern: impl Fn(usize) -> bool) {
+    for len in 0..=N::to_usize() {
+        let mut bitfield = Bitfield::<N>::new();
+        let mut bitlist = BitList::<N>::with_capacity(len).unwrap();
+        for i in 0..len {
+            bitfield.push(pattern(i)).unwrap();
+            bitlist.set(i, pattern(i)).unwrap();
+        }
+        bitfield.apply_updates().unwrap();
+
+        assert_eq!(bitfield.len(), len);
+        assert_eq!(bitfield.num_set_bits(), bitlist.num_set_bits());
+        assert!(bitfield.iter().eq(bitlist.iter()));
+        assert_eq!(bitfield.tree_hash_root(), bitlist.tree_hash_root());
+    }
+}
+
+#[test]
+fn tree_hash_matches_bitlist() {
+    check_against_bitlist::<U1>(|_| true);
+    check_against_bitlist::<U8>(|i| i % 3 == 0);
+    check_against_bitlist::<U256>(|i| i % 2 == 0);
+    check_against_bitlist::<U257>(|_| true);
+    check_against_bitlist::<U1024>(|i| i % 7 != 0);
+    check_against_bitlist::<U2048>(|i| i % 5 == 0);
+}
+
+#[test]
+fn get_set_push() {
+    let mut bitfield = Bitfield::<U8>::new();
+    assert!(bitfield.is_empty());
+    assert_eq!(
+        bitfield.get(0),
+        Err(Error::OutOfBounds { index: 0, len: 0 })
+    );
+
+    for _ in 0..8 {
+        bitfield.push(false).unwrap();
+    }
+    assert_eq!(bitfield.push(true), Err(Error::ListFull { len: 8 }));
+
+    bitfield.set(3, true).unwrap();
+    bitfield.set(7, true).unwrap();
+    assert_eq!(bitfield.get(3), Ok(true));
+    assert_eq!(bitfield.get(4), Ok(false));
+    assert_eq!(bitfield.num_set_bits(), 2);
+
+    bitfield.set(3, false).unwrap();
+    assert_eq!(bitfield.num_set_bits(), 1);
+    assert_eq!(
+        bitfield.set(8, true),
+        Err(Error::OutOfBounds { index: 8, len: 8 })
+    );
+}
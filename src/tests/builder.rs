@@ -1,6 +1,8 @@
-use crate::List;
-use tree_hash::Hash256;
-use typenum::{Unsigned, U16, U32};
+use crate::builder::Builder;
+use crate::utils::Length;
+use crate::{Error, List, Value, Vector};
+use tree_hash::{Hash256, TreeHash};
+use typenum::{Unsigned, U16, U32, U64, U8};
 
 #[test]
 fn build_partial_hash256_list() {
@@ -37,3 +39,316 @@ fn build_packed_u64_list() {
         assert_eq!(fast_list.iter().cloned().collect::<Vec<_>>(), sub_vec);
     }
 }
+
+#[test]
+fn push_many_matches_push() {
+    fn check<T: Value + std::fmt::Debug>(values: &[T]) {
+        let mut expected = List::<T, U32>::builder().unwrap();
+        for value in values {
+            expected.push(value.clone()).unwrap();
+        }
+        let expected = expected.finish().unwrap();
+
+        for split in 0..=values.len() {
+            let mut builder = List::<T, U32>::builder().unwrap();
+            for value in &values[..split] {
+                builder.push(value.clone()).unwrap();
+            }
+            builder.push_many(&values[split..]).unwrap();
+            assert_eq!(builder.finish().unwrap(), expected);
+        }
+    }
+
+    for len in 0..=32 {
+        check(&(0..len as u8).collect::<Vec<_>>());
+        check(&(0..len as u64).collect::<Vec<_>>());
+        check(
+            &(0..len as u64)
+                .map(|n| Hash256::right_padding_from(&n.to_le_bytes()))
+                .collect::<Vec<_>>(),
+        );
+    }
+}
+
+#[test]
+fn push_many_full() {
+    let mut builder = List::<u64, U16>::builder().unwrap();
+    builder.push_many(&[0; 10]).unwrap();
+    assert_eq!(builder.push_many(&[0; 7]), Err(Error::BuilderFull));
+    builder.push_many(&[0; 6]).unwrap();
+    let (_, _, length) = builder.finish().unwrap();
+    assert_eq!(length.as_usize(), 16);
+}
+
+#[test]
+fn from_tree_resumes_building() {
+    fn check<T: Value + Send + Sync + std::fmt::Debug>(values: &[T]) {
+        let expected = List::<T, U32>::try_from_iter(values.iter().cloned()).unwrap();
+
+        for split in 0..=values.len() {
+            let prefix = List::<T, U32>::try_from_iter(values[..split].iter().cloned()).unwrap();
+            let (tree, depth, length) = {
+                let mut builder = List::<T, U32>::builder().unwrap();
+                builder.push_many(&values[..split]).unwrap();
+                builder.finish().unwrap()
+            };
+            assert_eq!(length.as_usize(), prefix.len());
+
+            let mut builder = Builder::from_tree(tree, depth, length).unwrap();
+            builder.push_many(&values[split..]).unwrap();
+            let (tree, depth, length) = builder.finish().unwrap();
+            let list = List::<T, U32>::from_parts(tree, depth, length);
+            assert_eq!(list, expected);
+            assert_eq!(list.tree_hash_root(), expected.tree_hash_root());
+        }
+    }
+
+    for len in 0..=32 {
+        check(&(0..len as u8).collect::<Vec<_>>());
+        check(&(0..len as u64).collect::<Vec<_>>());
+        check(
+            &(0..len as u64)
+                .map(|n| Hash256::right_padding_from(&n.to_le_bytes()))
+                .collect::<Vec<_>>(),
+        );
+    }
+}
+
+#[test]
+fn from_tree_invalid() {
+    let list = List::<u64, U32>::try_from_iter(0..10).unwrap();
+    let (tree, depth) = (
+        list.interface.backing.tree.clone(),
+        list.interface.backing.depth,
+    );
+    assert_eq!(
+        Builder::from_tree(tree.clone(), depth, Length(33)).unwrap_err(),
+        Error::BuilderFull
+    );
+    assert_eq!(
+        Builder::from_tree(tree.clone(), depth + 1, Length(10)).unwrap_err(),
+        Error::BuilderInvalidTree
+    );
+    // The partial leaf must contain exactly the expected number of values.
+    assert_eq!(
+        Builder::from_tree(tree, depth, Length(9)).unwrap_err(),
+        Error::BuilderInvalidTree
+    );
+}
+
+#[test]
+fn vector_try_from_iter_wrong_length() {
+    type N = typenum::U33;
+    for len in [0, 1, 32] {
+        assert_eq!(
+            Vector::<u64, N>::try_from_iter(0..len as u64).unwrap_err(),
+            Error::WrongVectorLength { len, expected: 33 }
+        );
+    }
+    // Too long, but still within the capacity of the builder.
+    assert_eq!(
+        Vector::<u64, N>::try_from_iter(0..34).unwrap_err(),
+        Error::WrongVectorLength {
+            len: 34,
+            expected: 33
+        }
+    );
+    assert_eq!(
+        Vector::<u64, N>::try_from_iter(0..33).unwrap().to_vec(),
+        (0..33).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn exact_size_hint_fails_fast() {
+    // Iterators with an exact size hint are rejected before any element is consumed.
+    let unconsumed = |n: usize| (0..n).map(|_| -> u64 { panic!("iterator consumed") });
+    assert_eq!(
+        List::<u64, U16>::try_from_iter(unconsumed(17)).unwrap_err(),
+        Error::BuilderFull
+    );
+    assert_eq!(
+        Vector::<u64, U16>::try_from_iter(unconsumed(15)).unwrap_err(),
+        Error::WrongVectorLength {
+            len: 15,
+            expected: 16
+        }
+    );
+    assert_eq!(
+        Vector::<u64, U16>::try_from_iter(unconsumed(1 << 40)).unwrap_err(),
+        Error::WrongVectorLength {
+            len: 1 << 40,
+            expected: 16
+        }
+    );
+}
+
+#[test]
+fn from_fn() {
+    let vector = Vector::<u64, U16>::from_fn(|i| 1 << i).unwrap();
+    assert_eq!(
+        vector.to_vec(),
+        (0..16).map(|i| 1 << i).collect::<Vec<u64>>()
+    );
+
+    let list = List::<Hash256, U16>::from_fn(5, |i| Hash256::repeat_byte(i as u8)).unwrap();
+    assert_eq!(list.len(), 5);
+    assert_eq!(list.get(4), Some(&Hash256::repeat_byte(4)));
+
+    assert!(List::<u64, U16>::from_fn(0, |_| unreachable!())
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        List::<u64, U16>::from_fn(17, |_| unreachable!()).unwrap_err(),
+        Error::BuilderFull
+    );
+}
+
+#[test]
+fn list_map() {
+    let mut list = List::<u64, U16>::try_from_iter(0..10).unwrap();
+    *list.get_mut(3).unwrap() = 100;
+    list.push(10).unwrap();
+
+    let mapped = list
+        .map(|x| Hash256::right_padding_from(&x.to_le_bytes()))
+        .unwrap();
+    let expected = list
+        .iter()
+        .map(|x| Hash256::right_padding_from(&x.to_le_bytes()))
+        .collect::<Vec<_>>();
+    assert_eq!(mapped.len(), 11);
+    assert_eq!(mapped, List::<Hash256, U16>::new(expected).unwrap());
+
+    let empty = List::<u64, U16>::empty().map(|x| *x as u8).unwrap();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn map_shared_preserves_structure() {
+    let list = List::<u64, U64>::repeat(7, 48).unwrap();
+    let mapped = list.map_shared(|x| x * 2).unwrap();
+    assert_eq!(mapped.structure_stats(), list.structure_stats());
+    assert_eq!(
+        mapped.tree_hash_root(),
+        List::<u64, U64>::repeat_slow(14, 48)
+            .unwrap()
+            .tree_hash_root()
+    );
+
+    // Changing the packing rebuilds the tree.
+    let expected = List::<Hash256, U64>::repeat_slow(Hash256::repeat_byte(7), 48).unwrap();
+    let hashes = list.map_shared(|x| Hash256::repeat_byte(*x as u8)).unwrap();
+    assert_eq!(hashes.len(), 48);
+    assert_eq!(hashes.tree_hash_root(), expected.tree_hash_root());
+    let unpacked = hashes.map_shared(|x| x[0] as u64).unwrap();
+    assert_eq!(unpacked.tree_hash_root(), list.tree_hash_root());
+
+    // Pending updates are included.
+    let mut list = List::<Hash256, U8>::new(vec![Hash256::ZERO; 3]).unwrap();
+    list.push(Hash256::repeat_byte(1)).unwrap();
+    let mapped = list.map_shared(|x| Hash256::repeat_byte(x[0] + 1)).unwrap();
+    assert_eq!(
+        mapped.to_vec(),
+        vec![
+            Hash256::repeat_byte(1),
+            Hash256::repeat_byte(1),
+            Hash256::repeat_byte(1),
+            Hash256::repeat_byte(2)
+        ]
+    );
+}
+
+#[test]
+fn map_calls_f_per_element() {
+    let list = List::<u64, U64>::repeat(7, 48).unwrap();
+    let mut calls = 0;
+    let mapped = list
+        .map(|x| {
+            calls += 1;
+            x * 2
+        })
+        .unwrap();
+    assert_eq!(calls, 48);
+    assert_eq!(mapped.to_vec(), vec![14; 48]);
+}
+
+#[test]
+fn list_try_map() {
+    let list = List::<u64, U16>::try_from_iter(0..10).unwrap();
+
+    let mapped = list.try_map(|x| u8::try_from(*x)).unwrap().unwrap();
+    assert_eq!(mapped.to_vec(), (0..10).collect::<Vec<u8>>());
+
+    let mut calls = 0;
+    let result = list.try_map(|x| {
+        calls += 1;
+        if *x < 5 {
+            Ok(*x)
+        } else {
+            Err(*x)
+        }
+    });
+    assert_eq!(result, Ok(Err(5)));
+    assert_eq!(calls, 6);
+}
+
+#[test]
+fn try_from_iter_reporting() {
+    // Unknown length, packed and unpacked. The u8 builder could fit 32 values.
+    let unknown_len = |n: u64| (0..n).filter(|_| true);
+    assert_eq!(
+        List::<u8, U16>::try_from_iter_reporting(unknown_len(20).map(|x| x as u8)).unwrap_err(),
+        (Error::BuilderFull, 16)
+    );
+    assert_eq!(
+        List::<Hash256, U16>::try_from_iter_reporting(
+            unknown_len(17).map(|i| Hash256::repeat_byte(i as u8))
+        )
+        .unwrap_err(),
+        (Error::BuilderFull, 16)
+    );
+
+    // Exact size hints fail before consuming anything.
+    assert_eq!(
+        List::<u64, U16>::try_from_iter_reporting(0..100).unwrap_err(),
+        (Error::BuilderFull, 16)
+    );
+
+    // No more elements than necessary are consumed.
+    let mut iter = unknown_len(20);
+    List::<u64, U16>::try_from_iter_reporting(iter.by_ref()).unwrap_err();
+    assert_eq!(iter.next(), Some(17));
+
+    assert_eq!(
+        List::<u64, U16>::try_from_iter_reporting(unknown_len(16))
+            .unwrap()
+            .to_vec(),
+        (0..16).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn vector_map_and_zip_map() {
+    let mut a = Vector::<u64, U16>::from_fn(|i| i as u64).unwrap();
+    let b = Vector::<u64, U16>::from_fn(|i| 100 * i as u64).unwrap();
+
+    let doubled = a.map(|x| 2 * x).unwrap();
+    assert_eq!(doubled.to_vec(), (0..16).map(|i| 2 * i).collect::<Vec<_>>());
+
+    // Pending updates are included, and the element type may change.
+    *a.get_mut(3).unwrap() = 50;
+    let hashes = a.map(|x| Hash256::repeat_byte(*x as u8)).unwrap();
+    assert_eq!(hashes.get(3), Some(&Hash256::repeat_byte(50)));
+    assert_eq!(hashes.get(15), Some(&Hash256::repeat_byte(15)));
+
+    let sums = a.zip_map(&b, |x, y| x + y).unwrap();
+    assert_eq!(sums.get(2), Some(&202));
+    assert_eq!(sums.get(3), Some(&350));
+    assert_eq!(
+        sums.tree_hash_root(),
+        Vector::<u64, U16>::new(a.iter().zip(b.iter()).map(|(x, y)| x + y).collect())
+            .unwrap()
+            .tree_hash_root()
+    );
+}
@@ -0,0 +1,54 @@
+use crate::{Arc, List, Tree, Vector};
+use tree_hash::{Hash256, TreeHash};
+use typenum::U8;
+
+/// Assert that no node of `tree` is shared with `other`.
+fn assert_unshared<T: crate::Value>(tree: &Arc<Tree<T>>, other: &Arc<Tree<T>>) {
+    assert!(!Arc::ptr_eq(tree, other));
+    match (&**tree, &**other) {
+        (
+            Tree::Node { left, right, .. },
+            Tree::Node {
+                left: other_left,
+                right: other_right,
+                ..
+            },
+        ) => {
+            assert_unshared(left, other_left);
+            assert_unshared(right, other_right);
+        }
+        (Tree::Leaf(leaf), Tree::Leaf(other_leaf)) => {
+            assert!(!Arc::ptr_eq(&leaf.value, &other_leaf.value));
+        }
+        _ => (),
+    }
+}
+
+#[test]
+fn deep_clone_shares_nothing() {
+    let list = List::<Hash256, U8>::try_from_iter((0..5).map(Hash256::repeat_byte)).unwrap();
+    list.tree_hash_root();
+
+    let shallow = list.clone();
+    assert!(Arc::ptr_eq(
+        &shallow.interface.backing.tree,
+        &list.interface.backing.tree
+    ));
+
+    let mut deep = list.deep_clone();
+    assert_eq!(deep, list);
+    assert_unshared(&deep.interface.backing.tree, &list.interface.backing.tree);
+
+    // Cached hashes are kept.
+    assert_eq!(deep.dirty_node_count(), 0);
+    assert_eq!(deep.tree_hash_root(), list.tree_hash_root());
+
+    *deep.get_mut(0).unwrap() = Hash256::repeat_byte(0xff);
+    deep.apply_updates().unwrap();
+    assert_eq!(list.get(0), Some(&Hash256::repeat_byte(0)));
+
+    let vector = Vector::<u64, U8>::try_from_iter(0..8).unwrap();
+    let deep = vector.deep_clone();
+    assert_eq!(deep, vector);
+    assert_unshared(&deep.interface.backing.tree, &vector.interface.backing.tree);
+}
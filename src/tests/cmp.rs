@@ -0,0 +1,113 @@
+use super::list;
+use crate::{List, Vector};
+use ssz_derive::{Decode, Encode};
+use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use tree_hash_derive::TreeHash;
+use typenum::U16;
+
+#[test]
+fn cmp_elements_lexicographic() {
+    assert_eq!(list(&[1, 2]).cmp_elements(&list(&[1, 2])), Ordering::Equal);
+    assert_eq!(list(&[1, 2]).cmp_elements(&list(&[1, 3])), Ordering::Less);
+    assert_eq!(list(&[2]).cmp_elements(&list(&[1, 3])), Ordering::Greater);
+    // Shorter is less.
+    assert_eq!(
+        list(&[1, 2]).cmp_elements(&list(&[1, 2, 0])),
+        Ordering::Less
+    );
+    assert_eq!(list(&[]).cmp_elements(&list(&[0])), Ordering::Less);
+
+    // Pending updates are respected, and structure is ignored.
+    let mut l1 = list(&[1, 2]);
+    *l1.get_mut(1).unwrap() = 5;
+    assert_eq!(l1.cmp_elements(&list(&[1, 4])), Ordering::Greater);
+    assert_eq!(l1.cmp_elements(&list(&[1, 6])), Ordering::Less);
+    *l1.get_mut(1).unwrap() = 2;
+    assert_ne!(l1, list(&[1, 2]));
+    assert_eq!(l1.cmp_elements(&list(&[1, 2])), Ordering::Equal);
+}
+
+#[test]
+fn hash_element_sequence() {
+    let state = RandomState::new();
+    let mut l1 = list(&[1, 2]);
+    *l1.get_mut(1).unwrap() = 2;
+
+    assert_eq!(state.hash_one(list(&[1, 2])), state.hash_one(list(&[1, 2])));
+    assert_ne!(
+        state.hash_one(list(&[1, 2])),
+        state.hash_one(list(&[1, 2, 3]))
+    );
+
+    // Equal elements hash equally regardless of structure.
+    assert_eq!(state.hash_one(&l1), state.hash_one(list(&[1, 2])));
+}
+
+/// Value whose equality check panics, to detect element-wise comparisons.
+#[derive(Debug, Clone, Default, Encode, Decode, TreeHash)]
+struct NoEq {
+    x: u64,
+}
+
+impl PartialEq for NoEq {
+    fn eq(&self, _: &Self) -> bool {
+        panic!("element-wise comparison")
+    }
+}
+
+#[test]
+fn eq_shared_tree_fast_path() {
+    let l1 = List::<NoEq, U16>::new(vec![NoEq::default(); 10]).unwrap();
+    let l2 = l1.clone();
+    assert_eq!(l1, l2);
+
+    let v1 = Vector::<NoEq, U16>::from_elem(NoEq::default()).unwrap();
+    let v2 = v1.clone();
+    assert_eq!(v1, v2);
+}
+
+#[test]
+fn list_is_sorted_by() {
+    assert!(List::<u64, U16>::empty().is_sorted_by(Ord::cmp));
+    assert!(List::<u64, U16>::new(vec![7])
+        .unwrap()
+        .is_sorted_by(Ord::cmp));
+
+    let mut list = List::<u64, U16>::new(vec![1, 2, 2, 5]).unwrap();
+    assert!(list.is_sorted_by(Ord::cmp));
+    assert!(!list.is_sorted_by(|a, b| b.cmp(a)));
+
+    // Pending updates are visible.
+    list.push(4).unwrap();
+    assert!(!list.is_sorted_by(Ord::cmp));
+    *list.get_mut(4).unwrap() = 5;
+    assert!(list.is_sorted_by(Ord::cmp));
+
+    // Stops at the first out-of-order pair.
+    let list = List::<u64, U16>::new(vec![3, 1, 2, 0]).unwrap();
+    let mut comparisons = 0;
+    assert!(!list.is_sorted_by(|a, b| {
+        comparisons += 1;
+        a.cmp(b)
+    }));
+    assert_eq!(comparisons, 1);
+}
+
+#[test]
+fn eq_contents_ignores_pending_updates() {
+    let mut flushed = list(&[1, 2, 3]);
+    let mut pending = list(&[1, 2]);
+    pending.push(3).unwrap();
+
+    assert!(pending.eq_contents(&flushed));
+    assert_ne!(pending, flushed);
+
+    *flushed.get_mut(0).unwrap() = 1;
+    pending.apply_updates().unwrap();
+    assert!(flushed.eq_contents(&pending));
+
+    assert!(!list(&[1, 2]).eq_contents(&list(&[1, 2, 3])));
+    assert!(!list(&[1, 2, 4]).eq_contents(&list(&[1, 2, 3])));
+}
@@ -0,0 +1,136 @@
+use crate::{Error, List, Vector};
+use ssz_derive::{Decode, Encode};
+use std::cell::Cell;
+use tree_hash_derive::TreeHash;
+use typenum::U4;
+
+#[test]
+fn list_try_from_vec_and_slice() {
+    let list: List<u64, U4> = vec![1, 2, 3].try_into().unwrap();
+    assert_eq!(list.to_vec(), vec![1, 2, 3]);
+
+    let list = List::<u64, U4>::try_from(&[1, 2, 3, 4][..]).unwrap();
+    assert_eq!(list.to_vec(), vec![1, 2, 3, 4]);
+
+    let empty = List::<u64, U4>::try_from(vec![]).unwrap();
+    assert!(empty.is_empty());
+
+    assert_eq!(
+        List::<u64, U4>::try_from(vec![0; 5]),
+        Err(Error::ListFull { len: 4 })
+    );
+    assert_eq!(
+        List::<u64, U4>::try_from(&[0; 5][..]),
+        Err(Error::ListFull { len: 4 })
+    );
+}
+
+#[test]
+fn vector_try_from_vec_and_slice() {
+    let vector: Vector<u64, U4> = vec![1, 2, 3, 4].try_into().unwrap();
+    assert_eq!(vector.to_vec(), vec![1, 2, 3, 4]);
+
+    let vector = Vector::<u64, U4>::try_from(&[5, 6, 7, 8][..]).unwrap();
+    assert_eq!(vector.to_vec(), vec![5, 6, 7, 8]);
+
+    for len in [0, 3, 5] {
+        let expected = Err(Error::WrongVectorLength { len, expected: 4 });
+        assert_eq!(Vector::<u64, U4>::try_from(vec![0; len]), expected);
+        assert_eq!(Vector::<u64, U4>::try_from(&vec![0; len][..]), expected);
+    }
+}
+
+#[test]
+fn list_vector_conversion_len_consistent() {
+    let mut list = List::<u64, U4>::new(vec![1, 2]).unwrap();
+    list.debug_assert_len_consistent();
+    list.push(3).unwrap();
+    list.push(4).unwrap();
+
+    // The pushes are still pending, so they need to be applied before the check.
+    let mut vector = Vector::try_from(list).unwrap();
+    vector.apply_updates().unwrap();
+    vector.debug_assert_len_consistent();
+    *vector.get_mut(0).unwrap() = 10;
+
+    let mut list = List::from(vector);
+    list.apply_updates().unwrap();
+    list.debug_assert_len_consistent();
+    assert_eq!(list.to_vec(), vec![10, 2, 3, 4]);
+}
+
+thread_local! {
+    static CLONES: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A value which counts how many times it has been cloned on the current thread.
+#[derive(Debug, PartialEq, Encode, Decode, TreeHash)]
+struct Counted {
+    x: u64,
+}
+
+impl Clone for Counted {
+    fn clone(&self) -> Self {
+        CLONES.with(|clones| clones.set(clones.get() + 1));
+        Self { x: self.x }
+    }
+}
+
+fn clones() -> usize {
+    CLONES.with(Cell::get)
+}
+
+fn counted() -> Vec<Counted> {
+    (1..=3).map(|x| Counted { x }).collect()
+}
+
+#[test]
+fn list_into_vec_moves_unshared_values() {
+    let list = List::<Counted, U4>::new(counted()).unwrap();
+    let before = clones();
+    assert_eq!(list.into_vec(), counted());
+    assert_eq!(clones(), before);
+
+    // Values shared with another list are cloned.
+    let list = List::<Counted, U4>::new(counted()).unwrap();
+    let shared = list.clone();
+    let before = clones();
+    assert_eq!(list.into_vec(), shared.to_vec());
+    assert_eq!(clones(), before + 6);
+
+    // Pending updates are included.
+    let mut list = List::<u64, U4>::new(vec![1, 2]).unwrap();
+    list.push(3).unwrap();
+    assert_eq!(list.into_vec(), vec![1, 2, 3]);
+}
+
+#[cfg(feature = "ssz_types")]
+mod ssz_types_interop {
+    use crate::{List, Vector};
+    use ssz_types::{FixedVector, VariableList};
+    use tree_hash::{Hash256, TreeHash};
+    use typenum::{U1024, U33};
+
+    #[test]
+    fn variable_list_roundtrip() {
+        let var_list = VariableList::<u64, U1024>::new((0..1000).collect()).unwrap();
+        let list = List::<u64, U1024>::from(var_list.clone());
+        assert_eq!(list.tree_hash_root(), var_list.tree_hash_root());
+
+        let back = VariableList::try_from(list).unwrap();
+        assert_eq!(back, var_list);
+    }
+
+    #[test]
+    fn fixed_vector_roundtrip() {
+        let fixed = FixedVector::<Hash256, U33>::new(
+            (0..33).map(|i| Hash256::repeat_byte(i as u8)).collect(),
+        )
+        .unwrap();
+        let vector = Vector::<Hash256, U33>::from(fixed.clone());
+        assert_eq!(vector.tree_hash_root(), fixed.tree_hash_root());
+
+        let back = FixedVector::try_from(vector).unwrap();
+        assert_eq!(back, fixed);
+    }
+}
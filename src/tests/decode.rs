@@ -0,0 +1,159 @@
+use crate::{Error, List, Vector};
+use ssz::{Decode, Encode};
+use tree_hash::Hash256;
+use typenum::{U1024, U8};
+
+#[test]
+fn from_ssz_reader_matches_from_ssz_bytes() {
+    for len in [0, 1, 3, 4, 5, 100, 1024] {
+        let list = List::<u64, U1024>::try_from_iter(0..len).unwrap();
+        let bytes = list.as_ssz_bytes();
+
+        let from_reader = List::<u64, U1024>::from_ssz_reader(&bytes[..]).unwrap();
+        assert_eq!(from_reader, List::from_ssz_bytes(&bytes).unwrap());
+        assert_eq!(from_reader, list);
+    }
+
+    let list = List::<Hash256, U8>::try_from_iter((0..7).map(Hash256::repeat_byte)).unwrap();
+    let bytes = list.as_ssz_bytes();
+    assert_eq!(
+        List::<Hash256, U8>::from_ssz_reader(&bytes[..]).unwrap(),
+        list
+    );
+}
+
+#[test]
+fn from_ssz_reader_errors() {
+    // Too many items.
+    let bytes = List::<u64, U1024>::try_from_iter(0..9)
+        .unwrap()
+        .as_ssz_bytes();
+    assert_eq!(
+        List::<u64, U8>::from_ssz_reader(&bytes[..]),
+        Err(Error::ListFull { len: 8 })
+    );
+
+    // Trailing partial item.
+    assert_eq!(
+        List::<u64, U8>::from_ssz_reader(&bytes[..12]),
+        Err(Error::SszDecode {
+            error: ssz::DecodeError::InvalidByteLength {
+                len: 4,
+                expected: 8
+            }
+        })
+    );
+
+    // Variable-length items.
+    assert_eq!(
+        List::<List<u64, U8>, U8>::from_ssz_reader(&[][..]),
+        Err(Error::SszReaderVariableLength)
+    );
+}
+
+#[test]
+fn vector_from_ssz_bytes() {
+    let vector = Vector::<u64, U8>::try_from_iter(0..8).unwrap();
+    let bytes = vector.as_ssz_bytes();
+    assert_eq!(Vector::<u64, U8>::from_ssz_bytes(&bytes).unwrap(), vector);
+
+    for len in [0, 7, 9] {
+        let bytes = List::<u64, U1024>::try_from_iter(0..len)
+            .unwrap()
+            .as_ssz_bytes();
+        assert_eq!(
+            Vector::<u64, U8>::from_ssz_bytes(&bytes),
+            Err(ssz::DecodeError::InvalidByteLength {
+                len: 8 * len as usize,
+                expected: 64
+            })
+        );
+    }
+
+    // Variable-length items.
+    let vector = Vector::<List<u64, U8>, U8>::try_from_iter(
+        (0..8).map(|i| List::try_from_iter(0..i).unwrap()),
+    )
+    .unwrap();
+    let bytes = vector.as_ssz_bytes();
+    assert_eq!(
+        Vector::<List<u64, U8>, U8>::from_ssz_bytes(&bytes).unwrap(),
+        vector
+    );
+    assert!(Vector::<List<u64, U8>, typenum::U9>::from_ssz_bytes(&bytes).is_err());
+}
+
+#[test]
+fn from_ssz_bytes_limited() {
+    let list = List::<u64, U1024>::try_from_iter(0..9).unwrap();
+    let bytes = list.as_ssz_bytes();
+
+    assert_eq!(
+        List::<u64, U1024>::from_ssz_bytes_limited(&bytes, 9).unwrap(),
+        list
+    );
+    assert!(matches!(
+        List::<u64, U1024>::from_ssz_bytes_limited(&bytes, 8),
+        Err(Error::SszDecode {
+            error: ssz::DecodeError::BytesInvalid(_)
+        })
+    ));
+    // The type's own limit still applies.
+    assert!(List::<u64, U8>::from_ssz_bytes_limited(&bytes, 100).is_err());
+    assert_eq!(
+        List::<u64, U8>::from_ssz_bytes_limited(&[], 0).unwrap(),
+        List::empty()
+    );
+
+    // Variable-length items.
+    type Item = ssz_types::VariableList<u8, U8>;
+    let items = (0..3u8)
+        .map(|i| Item::new(vec![i; i as usize]).unwrap())
+        .collect::<Vec<_>>();
+    let list = List::<Item, U8>::new(items).unwrap();
+    let bytes = list.as_ssz_bytes();
+    assert_eq!(
+        List::<Item, U8>::from_ssz_bytes_limited(&bytes, 3).unwrap(),
+        list
+    );
+    assert!(List::<Item, U8>::from_ssz_bytes_limited(&bytes, 2).is_err());
+}
+
+#[test]
+fn variable_length_malformed_offsets() {
+    type Item = ssz_types::VariableList<u8, U8>;
+    type L = List<Item, U8>;
+
+    fn encode(offsets: &[u32], data: &[u8]) -> Vec<u8> {
+        offsets
+            .iter()
+            .flat_map(|offset| offset.to_le_bytes())
+            .chain(data.iter().copied())
+            .collect()
+    }
+
+    // A well-formed encoding of `[[1], [2, 3]]`.
+    let valid = encode(&[8, 9], &[1, 2, 3]);
+    let list = L::from_ssz_bytes(&valid).unwrap();
+    assert_eq!(list.as_ssz_bytes(), valid);
+    assert_eq!(list.len(), 2);
+
+    let malformed = [
+        // Decreasing offsets.
+        encode(&[8, 7], &[1, 2, 3]),
+        // Offset beyond the end of the input.
+        encode(&[8, 100], &[1, 2, 3]),
+        // Offset pointing into the offset table.
+        encode(&[8, 4], &[1, 2, 3]),
+        // First offset not a multiple of the offset size.
+        encode(&[6, 9], &[1, 2, 3]),
+        // First offset of zero.
+        encode(&[0], &[1, 2, 3]),
+        // Truncated offset table.
+        vec![8, 0],
+    ];
+    for bytes in malformed {
+        assert!(L::from_ssz_bytes(&bytes).is_err(), "{bytes:?}");
+        assert!(L::from_ssz_bytes_limited(&bytes, 8).is_err(), "{bytes:?}");
+    }
+}
@@ -0,0 +1,62 @@
+use super::list;
+use crate::{Arc, Deduplicator, Error, Tree};
+use tree_hash::TreeHash;
+
+#[test]
+fn dedup_across_lists() {
+    let values = (0..16).collect::<Vec<_>>();
+    let mut dedup = Deduplicator::new();
+    let mut l1 = list(&values);
+    let mut l2 = list(&values);
+    let mut l3 = list(&values.iter().map(|i| i % 8).collect::<Vec<_>>());
+
+    dedup.rebase(&mut l1).unwrap();
+    dedup.rebase(&mut l2).unwrap();
+    dedup.rebase(&mut l3).unwrap();
+
+    assert!(Arc::ptr_eq(
+        &l1.interface.backing.tree,
+        &l2.interface.backing.tree
+    ));
+
+    // The two halves of `l3` are identical to each other and to the left half of `l1`.
+    let Tree::Node { left, right, .. } = &*l3.interface.backing.tree else {
+        panic!("expected node");
+    };
+    let Tree::Node { left: l1_left, .. } = &*l1.interface.backing.tree else {
+        panic!("expected node");
+    };
+    assert!(Arc::ptr_eq(left, right));
+    assert!(Arc::ptr_eq(left, l1_left));
+
+    assert_eq!(l3.to_vec(), (0..16).map(|i| i % 8).collect::<Vec<_>>());
+}
+
+#[test]
+fn dedup_respects_length() {
+    let mut dedup = Deduplicator::new();
+    let mut l1 = list(&[0; 4]);
+    let mut l2 = list(&[0; 3]);
+    let l1_root = l1.tree_hash_root();
+    let l2_root = l2.tree_hash_root();
+
+    // Intern the shorter list first so that a length-insensitive key would corrupt `l1`.
+    dedup.rebase(&mut l2).unwrap();
+    dedup.rebase(&mut l1).unwrap();
+
+    assert_eq!(l1.to_vec(), vec![0; 4]);
+    assert_eq!(l2.to_vec(), vec![0; 3]);
+    assert_eq!(l1.tree_hash_root(), l1_root);
+    assert_eq!(l2.tree_hash_root(), l2_root);
+}
+
+#[test]
+fn dedup_pending_updates() {
+    let mut dedup = Deduplicator::new();
+    let mut list = list(&[1, 2, 3]);
+    list.push(4).unwrap();
+    assert_eq!(
+        dedup.rebase(&mut list),
+        Err(Error::DeduplicatePendingUpdates)
+    );
+}
@@ -0,0 +1,90 @@
+use crate::{Error, List, Vector};
+use typenum::U8;
+
+#[test]
+fn list_try_get() {
+    let mut list = List::<u64, U8>::new(vec![1, 2, 3]).unwrap();
+    assert_eq!(list.try_get(2), Ok(&3));
+    assert_eq!(
+        list.try_get(3),
+        Err(Error::OutOfBounds { index: 3, len: 3 })
+    );
+
+    // Pending pushes are visible.
+    list.push(4).unwrap();
+    assert_eq!(list.try_get(3), Ok(&4));
+    assert_eq!(
+        list.try_get(usize::MAX),
+        Err(Error::OutOfBounds {
+            index: usize::MAX,
+            len: 4
+        })
+    );
+}
+
+#[test]
+fn vector_try_get() {
+    let vector = Vector::<u64, U8>::from_elem(5).unwrap();
+    assert_eq!(vector.try_get(7), Ok(&5));
+    assert_eq!(
+        vector.try_get(8),
+        Err(Error::OutOfBounds { index: 8, len: 8 })
+    );
+}
+
+#[test]
+fn out_of_bounds_display() {
+    let list = List::<u64, U8>::new(vec![1, 2, 3]).unwrap();
+    let error: Box<dyn std::error::Error> = Box::new(list.try_get(5).unwrap_err());
+    assert_eq!(error.to_string(), "index 5 is out of bounds for length 3");
+}
+
+#[test]
+fn list_try_push_remaining_capacity() {
+    let mut list = List::<u64, U8>::new(vec![1, 2, 3]).unwrap();
+    assert_eq!(list.remaining_capacity(), 5);
+
+    for expected in (0..5).rev() {
+        assert_eq!(list.try_push(0), Ok(expected));
+    }
+    assert_eq!(list.remaining_capacity(), 0);
+    assert_eq!(list.try_push(0), Err(Error::ListFull { len: 8 }));
+
+    list.apply_updates().unwrap();
+    assert_eq!(list.remaining_capacity(), 0);
+}
+
+#[test]
+fn cow_is_mutable() {
+    let mut list = List::<u64, U8>::new(vec![1, 2, 3]).unwrap();
+
+    let mut cow = list.get_cow(1).unwrap();
+    assert!(!cow.is_mutable());
+    *cow.make_mut().unwrap() = 20;
+    assert!(cow.is_mutable());
+
+    // Once an update is pending the value is mutable without cloning.
+    let cow = list.get_cow(1).unwrap();
+    assert!(cow.is_mutable());
+    assert_eq!(*cow, 20);
+}
+
+#[test]
+fn list_get_or_push() {
+    let mut list = List::<u64, U8>::new(vec![1, 2]).unwrap();
+
+    *list.get_or_push(1, || unreachable!()).unwrap() += 10;
+    *list.get_or_push(2, || 3).unwrap() += 10;
+    assert_eq!(list.to_vec(), vec![1, 12, 13]);
+
+    assert_eq!(
+        list.get_or_push(4, || 5),
+        Err(Error::OutOfBounds { index: 4, len: 3 })
+    );
+
+    for index in 3..8 {
+        list.get_or_push(index, || index as u64).unwrap();
+    }
+    assert_eq!(list.get_or_push(8, || 8), Err(Error::ListFull { len: 8 }));
+    assert_eq!(list.len(), 8);
+}
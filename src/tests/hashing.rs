@@ -0,0 +1,314 @@
+use super::proptest::arb_index;
+use crate::{Error, List, Tree, Value, Vector};
+use proptest::prelude::*;
+use ssz_types::VariableList;
+use std::sync::Barrier;
+use tree_hash::{Hash256, TreeHash};
+use typenum::{U1024, U1048576, U16};
+
+#[test]
+fn list_tree_hash_pending_updates() {
+    let mut list = List::<u64, U16>::try_from_iter(0..10).unwrap();
+    let orig_root = list.tree_hash_root_cached().unwrap();
+    assert_eq!(orig_root, list.tree_hash_root());
+
+    *list.get_mut(3).unwrap() = 100;
+    list.push(11).unwrap();
+    assert_eq!(
+        list.tree_hash_root_cached(),
+        Err(Error::TreeHashPendingUpdates)
+    );
+
+    let flushed_root = list.tree_hash_root_flushing().unwrap();
+    assert_ne!(flushed_root, orig_root);
+    assert!(list.has_pending_updates());
+
+    list.apply_updates().unwrap();
+    assert_eq!(list.tree_hash_root(), flushed_root);
+    assert_eq!(list.tree_hash_root_cached(), Ok(flushed_root));
+}
+
+#[test]
+fn vector_tree_hash_pending_updates() {
+    let mut vector = Vector::<u64, U16>::try_from_iter(0..16).unwrap();
+
+    *vector.get_mut(15).unwrap() = 0;
+    assert_eq!(
+        vector.tree_hash_root_cached(),
+        Err(Error::TreeHashPendingUpdates)
+    );
+
+    let flushed_root = vector.tree_hash_root_flushing().unwrap();
+    vector.apply_updates().unwrap();
+    assert_eq!(vector.tree_hash_root(), flushed_root);
+}
+
+proptest! {
+    /// Mutating `k` elements must only dirty the `k` paths from those leaves to the root.
+    #[test]
+    fn dirty_paths_only(
+        indices in proptest::collection::vec(arb_index(1024), 0..32)
+    ) {
+        type N = U1024;
+        let mut list = List::<u64, N>::try_from_iter(0..1024).unwrap();
+        let mut vec = (0..1024).collect::<Vec<u64>>();
+        list.tree_hash_root();
+        assert_eq!(list.dirty_node_count(), 0);
+
+        for &i in &indices {
+            *list.get_mut(i).unwrap() += 1;
+            vec[i] += 1;
+        }
+        list.apply_updates().unwrap();
+
+        // Each path consists of the packed leaf and `depth` internal nodes.
+        let path_len = List::<u64, N>::depth() + 1;
+        assert!(list.dirty_node_count() <= indices.len() * path_len);
+
+        let var_list = VariableList::<u64, N>::new(vec).unwrap();
+        assert_eq!(list.tree_hash_root(), var_list.tree_hash_root());
+        assert_eq!(list.dirty_node_count(), 0);
+    }
+}
+
+#[test]
+fn readonly_tree_hash_does_not_cache() {
+    let list = List::<u64, U1024>::try_from_iter(0..1000).unwrap();
+    let var_list = VariableList::<u64, U1024>::new((0..1000).collect()).unwrap();
+
+    let dirty = list.dirty_node_count();
+    assert_eq!(
+        list.tree_hash_root_readonly().unwrap(),
+        var_list.tree_hash_root()
+    );
+    assert_eq!(list.dirty_node_count(), dirty);
+
+    // Once cached hashes exist they are reused.
+    assert_eq!(list.tree_hash_root(), var_list.tree_hash_root());
+    assert_eq!(list.dirty_node_count(), 0);
+    assert_eq!(
+        list.tree_hash_root_readonly().unwrap(),
+        var_list.tree_hash_root()
+    );
+}
+
+#[test]
+fn node_hash_internal_nodes() {
+    use ethereum_hashing::hash32_concat;
+
+    // 16 u64s are packed into 4 chunks, giving a tree of depth 2.
+    let list = List::<u64, U16>::try_from_iter(0..10).unwrap();
+    let root = list.tree_hash_root();
+    let chunks = (0..4)
+        .map(|i| list.node_hash(i, 0).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(chunks[3], Hash256::ZERO);
+
+    let left = list.node_hash(0, 1).unwrap();
+    let right = list.node_hash(1, 1).unwrap();
+    assert_eq!(
+        left,
+        Hash256::from(hash32_concat(chunks[0].as_slice(), chunks[1].as_slice()))
+    );
+    assert_eq!(
+        right,
+        Hash256::from(hash32_concat(chunks[2].as_slice(), chunks[3].as_slice()))
+    );
+
+    let tree_root = list.node_hash(0, 2).unwrap();
+    assert_eq!(tree_hash::mix_in_length(&tree_root, list.len()), root);
+
+    assert_eq!(list.node_hash(4, 0), None);
+    assert_eq!(list.node_hash(1, 2), None);
+    assert_eq!(list.node_hash(0, 3), None);
+}
+
+#[test]
+fn node_hash_vector_leaves() {
+    let values = (0..16).map(Hash256::repeat_byte).collect::<Vec<_>>();
+    let mut vector = Vector::<Hash256, U16>::new(values.clone()).unwrap();
+    for (i, value) in values.iter().enumerate() {
+        assert_eq!(vector.node_hash(i, 0), Some(*value));
+    }
+    assert_eq!(vector.node_hash(0, 4), Some(vector.tree_hash_root()));
+
+    *vector.get_mut(0).unwrap() = Hash256::ZERO;
+    assert_eq!(vector.node_hash(0, 0), None);
+}
+
+#[test]
+fn sequential_tree_hash_matches() {
+    type N = typenum::U65536;
+    for len in [0, 1, 100, 10_000, 65_536] {
+        let list = List::<u64, N>::try_from_iter(0..len as u64).unwrap();
+        let expected = VariableList::<u64, N>::new((0..len as u64).collect())
+            .unwrap()
+            .tree_hash_root();
+        assert_eq!(list.tree_hash_root_sequential(), Ok(expected));
+        // Cached hashes are reused by the parallel path.
+        assert_eq!(list.dirty_node_count(), 0);
+        assert_eq!(list.tree_hash_root(), expected);
+
+        let fresh = List::<u64, N>::try_from_iter(0..len as u64).unwrap();
+        assert_eq!(fresh.tree_hash_root(), expected);
+    }
+
+    let vector = Vector::<u64, U1024>::try_from_iter(0..1024).unwrap();
+    assert_eq!(
+        vector.tree_hash_root_sequential(),
+        Ok(vector.clone().tree_hash_root())
+    );
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn tree_hash_root_in_pool() {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+
+    let mut list = List::<u64, typenum::U65536>::try_from_iter(0..50_000).unwrap();
+    list.push(1).unwrap();
+    let root = list.tree_hash_root_in(&pool).unwrap();
+    assert!(!list.has_pending_updates());
+    assert_eq!(list.dirty_node_count(), 0);
+    assert_eq!(list.tree_hash_root(), root);
+
+    let mut vector = Vector::<u64, U1024>::try_from_iter(0..1024).unwrap();
+    *vector.get_mut(0).unwrap() = 7;
+    let root = vector.tree_hash_root_in(&pool).unwrap();
+    assert_eq!(vector.tree_hash_root(), root);
+}
+
+#[test]
+fn concurrent_tree_hash_shared_tree() {
+    let num_threads = 16;
+    for len in [1, 100, 4096, 50_000] {
+        let list = List::<u64, U1048576>::try_from_iter((0..len).map(|i| i * 3)).unwrap();
+        let hash256_list = List::<Hash256, U1048576>::try_from_iter(
+            (0..len).map(|i| Hash256::repeat_byte(i as u8)),
+        )
+        .unwrap();
+        let expected = list.deep_clone().tree_hash_root_sequential().unwrap();
+        let expected_hash256 = hash256_list
+            .deep_clone()
+            .tree_hash_root_sequential()
+            .unwrap();
+
+        // All threads hash the same unhashed tree at the same time.
+        let barrier = Barrier::new(num_threads);
+        let roots = std::thread::scope(|s| {
+            let handles = (0..num_threads)
+                .map(|_| {
+                    s.spawn(|| {
+                        barrier.wait();
+                        (list.tree_hash_root(), hash256_list.tree_hash_root())
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        for root in roots {
+            assert_eq!(root, (expected, expected_hash256));
+        }
+        assert_eq!(list.dirty_node_count(), 0);
+        assert_eq!(list.tree_hash_root(), expected);
+        assert_eq!(hash256_list.tree_hash_root(), expected_hash256);
+    }
+}
+
+#[test]
+fn index_by_tree_hash() {
+    let mut list =
+        List::<Hash256, U16>::try_from_iter((0..6).map(|i| Hash256::repeat_byte(i % 4))).unwrap();
+    list.tree_hash_root();
+
+    let index = list.index_by_tree_hash();
+    assert_eq!(index.len(), 4);
+    assert_eq!(index[&Hash256::repeat_byte(0)], 4);
+    assert_eq!(index[&Hash256::repeat_byte(1)], 5);
+    assert_eq!(index[&Hash256::repeat_byte(3)], 3);
+
+    // Pending updates replace the hashes of the backing values.
+    *list.get_mut(4).unwrap() = Hash256::repeat_byte(9);
+    list.push(Hash256::repeat_byte(2)).unwrap();
+    let index = list.index_by_tree_hash();
+    assert_eq!(index[&Hash256::repeat_byte(0)], 0);
+    assert_eq!(index[&Hash256::repeat_byte(9)], 4);
+    assert_eq!(index[&Hash256::repeat_byte(2)], 6);
+
+    let packed = List::<u64, U16>::try_from_iter([5, 6, 5]).unwrap();
+    let index = packed.index_by_tree_hash();
+    assert_eq!(index.len(), 2);
+    assert_eq!(index[&5u64.tree_hash_root()], 2);
+    assert_eq!(index[&6u64.tree_hash_root()], 1);
+}
+
+#[test]
+fn verify_root() {
+    let mut list = List::<u64, U16>::try_from_iter(0..10).unwrap();
+    let root = list.tree_hash_root();
+    assert_eq!(list.verify_root(root), Ok(()));
+    assert_eq!(
+        list.verify_root(Hash256::ZERO),
+        Err(Error::RootMismatch {
+            expected: Hash256::ZERO,
+            actual: root
+        })
+    );
+    list.push(10).unwrap();
+    assert_eq!(list.verify_root(root), Err(Error::TreeHashPendingUpdates));
+
+    let vect = Vector::<u64, U16>::try_from_iter(0..16).unwrap();
+    let root = vect.tree_hash_root();
+    assert_eq!(vect.verify_root(root), Ok(()));
+    assert!(matches!(
+        vect.verify_root(Hash256::repeat_byte(1)),
+        Err(Error::RootMismatch { .. })
+    ));
+}
+
+/// Count the nodes and leaves which will be hashed by the next call to `tree_hash`.
+fn unhashed_nodes<T: Value>(tree: &Tree<T>) -> usize {
+    match tree {
+        Tree::Node { hash, left, right } if hash.read().is_zero() => {
+            1 + unhashed_nodes(left) + unhashed_nodes(right)
+        }
+        Tree::Leaf(leaf) if leaf.hash.read().is_zero() => 1,
+        Tree::PackedLeaf(leaf) if leaf.hash.read().is_zero() => 1,
+        _ => 0,
+    }
+}
+
+#[test]
+fn single_edit_rehashes_one_path() {
+    let list = List::<u64, U1048576>::try_from_iter(0..1_000_000).unwrap();
+    list.tree_hash_root();
+    let hash_list =
+        List::<Hash256, U1048576>::try_from_iter((0..1000).map(|i| Hash256::repeat_byte(i as u8)))
+            .unwrap();
+    hash_list.tree_hash_root();
+
+    for index in [0, 123_456, 999_999] {
+        let mut list = list.clone();
+        *list.get_mut(index).unwrap() += 1;
+        list.apply_updates().unwrap();
+        assert_eq!(
+            unhashed_nodes(&list.interface.backing.tree),
+            list.interface.backing.depth + 1
+        );
+    }
+
+    let mut hash_list = hash_list.clone();
+    *hash_list.get_mut(500).unwrap() = Hash256::repeat_byte(0xff);
+    hash_list.apply_updates().unwrap();
+    assert_eq!(
+        unhashed_nodes(&hash_list.interface.backing.tree),
+        hash_list.interface.backing.depth + 1
+    );
+}
@@ -1,5 +1,5 @@
 use crate::{Error, List, Vector};
-use tree_hash::Hash256;
+use tree_hash::{Hash256, TreeHash};
 use typenum::{Unsigned, U64};
 
 #[test]
@@ -75,3 +75,178 @@ fn hash256_vector_iter_from() {
         }
     );
 }
+
+#[test]
+fn iter_mut_list() {
+    let mut list = List::<u64, U64>::try_from_iter(0..40).unwrap();
+    *list.get_mut(5).unwrap() = 100;
+    list.push(40).unwrap();
+
+    for x in list.iter_mut() {
+        *x += 1;
+    }
+
+    let mut expected = (1..42).collect::<Vec<u64>>();
+    expected[5] = 101;
+    assert_eq!(list.to_vec(), expected);
+    assert_eq!(list.pending_len(), 41);
+
+    list.apply_updates().unwrap();
+    assert_eq!(list.to_vec(), expected);
+}
+
+#[test]
+fn iter_mut_vector() {
+    let mut vector = Vector::<u64, U64>::from_elem(3).unwrap();
+    for (i, x) in vector.iter_mut().enumerate() {
+        *x *= i as u64;
+    }
+    vector.apply_updates().unwrap();
+    assert_eq!(
+        vector.to_vec(),
+        (0..64).map(|i| 3 * i).collect::<Vec<u64>>()
+    );
+}
+
+#[test]
+fn fold_subtrees_skips_zero_subtrees() {
+    type N = typenum::U1048576;
+    let len = 100_000;
+    let populated = [5, 70_000, 99_999];
+    let mut list = List::<u64, N>::repeat(0, len).unwrap();
+    for &i in &populated {
+        *list.get_mut(i).unwrap() = i as u64;
+    }
+    list.apply_updates().unwrap();
+
+    let count_nonzero = |list: &List<u64, N>| {
+        list.fold_subtrees(
+            (0, 0),
+            |(nonzero, visited), x| (nonzero + usize::from(*x != 0), visited + 1),
+            |(nonzero, visited), count| (nonzero, visited + count),
+        )
+    };
+
+    // Before hashing every element is visited.
+    assert_eq!(count_nonzero(&list), Ok((populated.len(), len)));
+
+    // After hashing the zero subtrees are skipped, but still counted.
+    list.tree_hash_root();
+    assert_eq!(count_nonzero(&list), Ok((populated.len(), len)));
+    let leaves_visited = list
+        .fold_subtrees(0, |visited, _| visited + 1, |visited, _| visited)
+        .unwrap();
+    assert!(leaves_visited < 1000);
+
+    list.push(1).unwrap();
+    assert_eq!(count_nonzero(&list), Err(Error::FoldPendingUpdates));
+}
+
+#[test]
+fn list_windows() {
+    let mut list = List::<u64, U64>::try_from_iter(0..5).unwrap();
+    *list.get_mut(2).unwrap() = 20;
+    list.push(5).unwrap();
+
+    let windows = list
+        .windows(3)
+        .map(|w| w.into_iter().copied().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        windows,
+        vec![
+            vec![0, 1, 20],
+            vec![1, 20, 3],
+            vec![20, 3, 4],
+            vec![3, 4, 5]
+        ]
+    );
+
+    assert_eq!(list.windows(1).count(), 6);
+    assert_eq!(list.windows(6).count(), 1);
+    assert_eq!(list.windows(7).count(), 0);
+    assert_eq!(List::<u64, U64>::empty().windows(1).count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "window size must be non-zero")]
+fn list_windows_zero_size() {
+    let list = List::<u64, U64>::try_from_iter(0..5).unwrap();
+    let _ = list.windows(0);
+}
+
+#[test]
+fn vector_rotate() {
+    type N = typenum::U33;
+    let n = N::to_usize();
+    let values = (0..n as u64).collect::<Vec<_>>();
+
+    for mid in [0, 1, 4, 16, 32, 33, 34, 100] {
+        let mut vector = Vector::<u64, N>::new(values.clone()).unwrap();
+        // Pending updates are included in the rotation.
+        *vector.get_mut(0).unwrap() = 1000;
+        let mut expected = values.clone();
+        expected[0] = 1000;
+
+        let mut left = vector.clone();
+        left.rotate_left(mid).unwrap();
+        let mut expected_left = expected.clone();
+        expected_left.rotate_left(mid % n);
+        assert_eq!(left.to_vec(), expected_left);
+        assert_eq!(
+            left.tree_hash_root(),
+            Vector::<u64, N>::new(expected_left)
+                .unwrap()
+                .tree_hash_root()
+        );
+
+        let mut right = vector.clone();
+        right.rotate_right(mid).unwrap();
+        let mut expected_right = expected.clone();
+        expected_right.rotate_right(mid % n);
+        assert_eq!(right.to_vec(), expected_right);
+    }
+}
+
+#[test]
+fn list_iter_arc_and_get_arc() {
+    let mut list = List::<Hash256, U64>::try_from_iter((0..10).map(Hash256::repeat_byte)).unwrap();
+
+    // Values from the tree are shared.
+    let arc = list.get_arc(3).unwrap();
+    assert_eq!(*arc, Hash256::repeat_byte(3));
+    assert!(crate::Arc::ptr_eq(&arc, &list.get_arc(3).unwrap()));
+
+    *list.get_mut(3).unwrap() = Hash256::ZERO;
+    list.push(Hash256::repeat_byte(10)).unwrap();
+    assert_eq!(list.get_arc(3).as_deref(), Some(&Hash256::ZERO));
+    assert_eq!(list.get_arc(10).as_deref(), Some(&Hash256::repeat_byte(10)));
+    assert_eq!(list.get_arc(11), None);
+
+    let values = list.iter_arc().unwrap().collect::<Vec<_>>();
+    assert_eq!(values.len(), 11);
+    assert!(values.iter().map(|arc| &**arc).eq(list.iter()));
+
+    // Packed values aren't stored in arcs.
+    let packed = List::<u64, U64>::try_from_iter(0..10).unwrap();
+    assert_eq!(packed.get_arc(0), None);
+    assert!(matches!(packed.iter_arc(), Err(Error::ArcIterPacked)));
+}
+
+#[test]
+fn list_stride() {
+    let mut list = List::<u64, U64>::try_from_iter(0..50).unwrap();
+    *list.get_mut(10).unwrap() = 1000;
+    list.push(50).unwrap();
+
+    for start in [0, 1, 10, 49, 50, 51, 100] {
+        for step in [1, 3, 10, 64] {
+            let expected = list.iter().skip(start).step_by(step).collect::<Vec<_>>();
+            assert_eq!(list.stride(start, step).collect::<Vec<_>>(), expected);
+        }
+    }
+    assert_eq!(
+        list.stride(0, 10).copied().collect::<Vec<_>>(),
+        vec![0, 1000, 20, 30, 40, 50]
+    );
+}
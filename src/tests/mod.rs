@@ -1,9 +1,30 @@
 #![cfg(test)]
 
+use crate::List;
+use typenum::U16;
+
+mod apply_updates;
+mod bitfield;
 mod builder;
+mod clone;
+mod cmp;
+mod conversions;
+mod decode;
+mod dedup;
+mod get;
+mod hashing;
 mod iterator;
 mod packed;
 mod pop_front;
 mod proptest;
+mod rebase;
 mod repeat;
+mod serde_with;
 mod size_of;
+mod tree;
+mod update_map;
+
+/// List of `u64`s with capacity 16, shared by the test modules.
+fn list(values: &[u64]) -> List<u64, U16> {
+    List::new(values.to_vec()).unwrap()
+}
@@ -1,6 +1,7 @@
-use crate::{List, Vector};
+use crate::{Error, List, PackedLeaf, Vector};
+use ssz::Encode;
 use ssz_types::{FixedVector, VariableList};
-use tree_hash::TreeHash;
+use tree_hash::{Hash256, TreeHash};
 use typenum::U16;
 
 #[test]
@@ -83,3 +84,106 @@ fn out_of_order_mutations() {
 
     assert_eq!(list.to_vec(), vec);
 }
+
+#[test]
+fn packed_leaf_accessors() {
+    let mut leaf = PackedLeaf::<u64>::empty();
+    assert!(leaf.as_slice().is_empty());
+
+    leaf.push(7).unwrap();
+    leaf.push(9).unwrap();
+    assert_eq!(leaf.as_slice(), &[7, 9]);
+    assert_eq!(leaf.iter().copied().collect::<Vec<_>>(), vec![7, 9]);
+}
+
+#[test]
+fn packed_leaf_from_slice() {
+    let leaf = PackedLeaf::<u64>::from_slice(&[1, 2, 3, 4]).unwrap();
+    assert_eq!(leaf.as_slice(), &[1, 2, 3, 4]);
+
+    assert_eq!(
+        PackedLeaf::<u64>::from_slice(&[1, 2, 3, 4, 5]),
+        Err(Error::PackedLeafFull { len: 5 })
+    );
+}
+
+fn check_packed_ssz<T>(values: Vec<T>)
+where
+    T: crate::Value + Send + Sync + std::fmt::Debug,
+{
+    let expected = VariableList::<T, U16>::new(values.clone())
+        .unwrap()
+        .as_ssz_bytes();
+
+    // Unhashed leaves are encoded element by element.
+    let list = List::<T, U16>::new(values.clone()).unwrap();
+    assert_eq!(list.as_ssz_bytes(), expected);
+
+    // Hashed leaves are copied from their hashes.
+    list.tree_hash_root();
+    assert_eq!(list.as_ssz_bytes(), expected);
+
+    let vector_values = values.iter().cloned().cycle().take(16).collect::<Vec<_>>();
+    let vector = Vector::<T, U16>::new(vector_values.clone()).unwrap();
+    vector.tree_hash_root();
+    assert_eq!(
+        vector.as_ssz_bytes(),
+        FixedVector::<T, U16>::new(vector_values)
+            .unwrap()
+            .as_ssz_bytes()
+    );
+}
+
+#[test]
+fn packed_ssz_encoding_matches_generic() {
+    for len in 1..=16 {
+        check_packed_ssz((0..len).map(|i| i as u8 * 17).collect());
+        check_packed_ssz((0..len).map(|i| i as u16 * 1001).collect());
+        check_packed_ssz((0..len).map(|i| u64::MAX - i).collect());
+        check_packed_ssz((0..len).map(|i| i % 3 == 0).collect());
+    }
+}
+
+#[test]
+fn packed_leaf_update_gap() {
+    let updates = std::collections::BTreeMap::from([(4, 10u64), (6, 12)]);
+    assert_eq!(
+        PackedLeaf::<u64>::empty()
+            .update(4, Hash256::ZERO, &updates)
+            .unwrap_err(),
+        Error::PackedLeafUpdateGap {
+            index: 6,
+            next_index: 5
+        }
+    );
+
+    // Contiguous updates may extend the leaf.
+    let updates = std::collections::BTreeMap::from([(4, 10u64), (5, 11), (6, 12)]);
+    let leaf = PackedLeaf::from_slice(&[1u64])
+        .unwrap()
+        .update(4, Hash256::ZERO, &updates)
+        .unwrap();
+    assert_eq!(leaf.as_slice(), &[10, 11, 12]);
+}
+
+#[test]
+fn list_chunk_leaves() {
+    let mut list = List::<u64, U16>::try_from_iter(0..10).unwrap();
+    let chunks = list.chunk_leaves().unwrap().collect::<Vec<_>>();
+    assert_eq!(
+        chunks,
+        vec![&[0, 1, 2, 3][..], &[4, 5, 6, 7][..], &[8, 9][..]]
+    );
+
+    list.push(10).unwrap();
+    assert!(matches!(
+        list.chunk_leaves(),
+        Err(Error::ChunkLeavesPendingUpdates)
+    ));
+
+    assert_eq!(List::<u64, U16>::empty().chunk_leaves().unwrap().count(), 0);
+    assert!(matches!(
+        List::<Hash256, U16>::empty().chunk_leaves(),
+        Err(Error::ChunkLeavesNotPacked)
+    ));
+}
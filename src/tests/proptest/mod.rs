@@ -8,6 +8,7 @@ use typenum::{Unsigned, U4};
 mod operations;
 mod rebase;
 mod tree_hash_and_ssz;
+mod zip;
 
 pub fn arb_index(n: usize) -> impl Strategy<Value = usize> {
     any::<proptest::sample::Index>().prop_map(move |index| index.index(n))
@@ -249,7 +249,7 @@ where
                     // NOTE: we can't assert deep equality here at the moment because vectors and
                     // lists store their lengths differently, and this shows up in the roundtrip
                     // process when there are pending updates.
-                    assert!(list.iter().eq(re_list.iter()));
+                    assert!(list.eq_contents(&re_list));
                 }
             }
         }
@@ -112,6 +112,45 @@ mod list {
     list_test!(large_1024, Large, U1024, arb_large());
 }
 
+/// Lists of variable-length items, checked against nested `VariableList`s.
+macro_rules! nested_list_test {
+    ($name:ident, $N:ty, $M:ty) => {
+        proptest! {
+            #[test]
+            fn $name(
+                init in arb_list::<_, $N, _>(arb_list::<u8, $M, _>(any::<u8>()))
+            ) {
+                let list = List::<List<u8, $M>, $N>::new(
+                    init.iter().map(|inner| List::new(inner.clone()).unwrap()).collect()
+                ).unwrap();
+                let var_list = VariableList::<VariableList<u8, $M>, $N>::new(
+                    init.into_iter().map(|inner| VariableList::new(inner).unwrap()).collect()
+                ).unwrap();
+
+                let ssz_bytes = list.as_ssz_bytes();
+                assert_eq!(ssz_bytes, var_list.as_ssz_bytes());
+
+                // SSZ roundtrip
+                let decoded = List::<List<u8, $M>, $N>::from_ssz_bytes(&ssz_bytes).unwrap();
+                assert_eq!(decoded, list);
+                assert_eq!(decoded.len(), list.len());
+
+                // Tree hash of the decoded list matches VariableList
+                assert_eq!(decoded.tree_hash_root(), var_list.tree_hash_root());
+            }
+        }
+    };
+}
+
+mod nested_list {
+    use super::*;
+
+    nested_list_test!(u8_1_4, U1, U4);
+    nested_list_test!(u8_3_4, U3, U4);
+    nested_list_test!(u8_9_1, U9, U1);
+    nested_list_test!(u8_33_32, U33, U32);
+}
+
 mod vect {
     use super::*;
 
@@ -0,0 +1,102 @@
+use super::{arb_hash256, arb_index, arb_list};
+use crate::List;
+use proptest::prelude::*;
+use tree_hash::{Hash256, TreeHash};
+use typenum::{U1, U1024, U2, U32, U33, U7, U8};
+
+macro_rules! zip_test {
+    ($name:ident, $T:ty, $N:ty) => {
+        // Use default strategy (assumes existence of an `Arbitrary` impl).
+        zip_test!($name, $T, $N, any::<$T>());
+    };
+    ($name:ident, $T:ty, $N:ty, $strat:expr) => {
+        proptest! {
+            #[test]
+            fn $name(
+                orig_vec in arb_list::<$T, $N, _>(&$strat),
+                other_vec in arb_list::<$T, $N, _>(&$strat),
+                updates in proptest::collection::vec(
+                    (arb_index(<$N as typenum::Unsigned>::to_usize()), &$strat),
+                    0..16,
+                ),
+            ) {
+                let orig = List::<$T, $N>::new(orig_vec).unwrap();
+                let other = List::<$T, $N>::new(other_vec).unwrap();
+
+                // Unrelated lists.
+                assert!(orig.zip(&other).eq(orig.iter().zip(other.iter())));
+                let unshared = orig.zip_unshared(&other).unwrap().collect::<Vec<_>>();
+                let expected = orig
+                    .iter()
+                    .zip(other.iter())
+                    .enumerate()
+                    .map(|(i, (x, y))| (i, x, y))
+                    .collect::<Vec<_>>();
+                assert_eq!(unshared, expected);
+
+                // Mostly shared lists.
+                let mut modified = orig.clone();
+                for (index, value) in updates {
+                    if let Some(x) = modified.get_mut(index) {
+                        *x = value;
+                    }
+                }
+                modified.apply_updates().unwrap();
+                assert!(orig.zip(&modified).eq(orig.iter().zip(modified.iter())));
+
+                let unshared = orig.zip_unshared(&modified).unwrap().collect::<Vec<_>>();
+                assert!(unshared.windows(2).all(|w| w[0].0 < w[1].0));
+                for &(i, x, y) in &unshared {
+                    assert_eq!(orig.get(i), Some(x));
+                    assert_eq!(modified.get(i), Some(y));
+                }
+                for (i, (x, y)) in orig.iter().zip(modified.iter()).enumerate() {
+                    if x != y {
+                        assert!(unshared.iter().any(|&(j, _, _)| i == j));
+                    }
+                }
+
+                // Diff indices, before and after hashing.
+                let expected_diff = orig
+                    .iter()
+                    .zip(modified.iter())
+                    .enumerate()
+                    .filter(|(_, (x, y))| x != y)
+                    .map(|(i, _)| i)
+                    .collect::<Vec<_>>();
+                let diff = orig.diff_indices(&modified).unwrap().collect::<Vec<_>>();
+                assert_eq!(diff, expected_diff);
+                orig.tree_hash_root();
+                modified.tree_hash_root();
+                let diff = orig.diff_indices(&modified).unwrap().collect::<Vec<_>>();
+                assert_eq!(diff, expected_diff);
+
+                let expected_diff = orig
+                    .iter()
+                    .zip(other.iter())
+                    .enumerate()
+                    .filter(|(_, (x, y))| x != y)
+                    .map(|(i, _)| i)
+                    .chain(orig.len().min(other.len())..orig.len().max(other.len()))
+                    .collect::<Vec<_>>();
+                let diff = orig.diff_indices(&other).unwrap().collect::<Vec<_>>();
+                assert_eq!(diff, expected_diff);
+            }
+        }
+    };
+}
+
+zip_test!(u8_1, u8, U1);
+zip_test!(u8_2, u8, U2);
+zip_test!(u8_7, u8, U7);
+zip_test!(u8_33, u8, U33);
+zip_test!(u8_1024, u8, U1024);
+
+zip_test!(u64_1, u64, U1);
+zip_test!(u64_8, u64, U8);
+zip_test!(u64_33, u64, U33);
+zip_test!(u64_1024, u64, U1024);
+
+zip_test!(hash256_1, Hash256, U1, arb_hash256());
+zip_test!(hash256_32, Hash256, U32, arb_hash256());
+zip_test!(hash256_1024, Hash256, U1024, arb_hash256());
@@ -0,0 +1,97 @@
+use crate::tree::RebaseStats;
+use crate::{Arc, Error, List};
+use tree_hash::TreeHash;
+use typenum::{U1000, U1024, U16, U32};
+
+#[test]
+fn rebase_stats_equal() {
+    let orig = List::<u64, U16>::try_from_iter(0..16).unwrap();
+    let base = List::<u64, U16>::try_from_iter(0..16).unwrap();
+
+    let mut rebased = orig.clone();
+    let stats = rebased.rebase_on_with_stats(&base).unwrap();
+
+    assert_eq!(
+        stats,
+        RebaseStats {
+            nodes_replaced: 1,
+            nodes_kept: 0,
+            leaves_compared: 4,
+        }
+    );
+    assert!(Arc::ptr_eq(
+        &rebased.interface.backing.tree,
+        &base.interface.backing.tree
+    ));
+    assert_eq!(rebased, orig);
+}
+
+#[test]
+fn rebase_stats_one_change() {
+    let base = List::<u64, U16>::try_from_iter(0..16).unwrap();
+    let orig =
+        List::<u64, U16>::try_from_iter((0..16).map(|i| if i == 0 { 100 } else { i })).unwrap();
+
+    let mut rebased = orig.clone();
+    let stats = rebased.rebase_on_with_stats(&base).unwrap();
+
+    // The first packed leaf and its two ancestors differ, one sibling leaf and one sibling
+    // subtree are grafted from the base.
+    assert_eq!(
+        stats,
+        RebaseStats {
+            nodes_replaced: 2,
+            nodes_kept: 3,
+            leaves_compared: 4,
+        }
+    );
+    assert_eq!(rebased, orig);
+}
+
+#[test]
+fn rebase_on_list_different_capacity() {
+    let base = List::<u64, U1024>::try_from_iter(0..600).unwrap();
+    let mut list = List::<u64, U1000>::try_from_iter(0..600).unwrap();
+
+    list.rebase_on_list(&base).unwrap();
+    assert!(Arc::ptr_eq(
+        &list.interface.backing.tree,
+        &base.interface.backing.tree
+    ));
+    assert_eq!(list.to_vec(), base.to_vec());
+}
+
+#[test]
+fn rebase_on_list_depth_mismatch() {
+    let base = List::<u64, U32>::try_from_iter(0..16).unwrap();
+    let mut list = List::<u64, U16>::try_from_iter(0..16).unwrap();
+
+    assert_eq!(
+        list.rebase_on_list(&base),
+        Err(Error::RebaseDepthMismatch {
+            depth: 2,
+            base_depth: 3
+        })
+    );
+}
+
+#[test]
+fn snapshot_restore() {
+    let mut list = List::<u64, U16>::try_from_iter(0..10).unwrap();
+    list.push(10).unwrap();
+    let snapshot = list.snapshot().unwrap();
+    assert!(list.has_pending_updates());
+    list.apply_updates().unwrap();
+    let root = list.tree_hash_root();
+
+    *list.get_mut(3).unwrap() = 100;
+    list.push(11).unwrap();
+    list.apply_updates().unwrap();
+    assert_ne!(list.tree_hash_root(), root);
+    list.push(12).unwrap();
+
+    list.restore(&snapshot);
+    assert!(!list.has_pending_updates());
+    assert_eq!(list.tree_hash_root(), root);
+    assert_eq!(list.to_vec(), (0..11).collect::<Vec<_>>());
+}
@@ -1,4 +1,4 @@
-use crate::{List, Value};
+use crate::{List, Value, Vector};
 use std::fmt::Debug;
 use tree_hash::TreeHash;
 use typenum::{Unsigned, U1024, U64, U8};
@@ -39,3 +39,54 @@ fn list_list_u8_64() {
     list_test::<List<u8, U8>, U64>(List::repeat(1u8, 1).unwrap());
     list_test::<List<u8, U8>, U64>(List::repeat(255u8, 8).unwrap());
 }
+
+#[test]
+fn list_fill() {
+    let mut list = List::<u64, U64>::try_from_iter(0..40).unwrap();
+    *list.get_mut(3).unwrap() = 1000;
+    list.push(40).unwrap();
+
+    list.fill(7).unwrap();
+    assert!(!list.has_pending_updates());
+    assert_eq!(list, List::repeat_slow(7, 41).unwrap());
+    assert_eq!(
+        list.tree_hash_root(),
+        List::<u64, U64>::repeat_slow(7, 41)
+            .unwrap()
+            .tree_hash_root()
+    );
+}
+
+#[test]
+fn vector_fill() {
+    let mut vec = Vector::<u8, U64>::try_from_iter(0..64).unwrap();
+    *vec.get_mut(10).unwrap() = 0;
+
+    vec.fill(255).unwrap();
+    assert!(vec.iter().all(|x| *x == 255));
+    assert_eq!(vec, Vector::from_elem(255).unwrap());
+}
+
+#[test]
+fn list_dedup() {
+    let mut list = List::<u64, U64>::new(vec![1, 1, 2, 3, 3, 3, 1, 4, 4]).unwrap();
+    *list.get_mut(8).unwrap() = 5;
+    list.push(5).unwrap();
+    list.dedup().unwrap();
+    assert!(!list.has_pending_updates());
+    assert_eq!(list.to_vec(), vec![1, 2, 3, 1, 4, 5]);
+    assert_eq!(
+        list.tree_hash_root(),
+        List::<u64, U64>::new(vec![1, 2, 3, 1, 4, 5])
+            .unwrap()
+            .tree_hash_root()
+    );
+
+    let mut all_equal = List::<u64, U64>::repeat(7, 64).unwrap();
+    all_equal.dedup().unwrap();
+    assert_eq!(all_equal.to_vec(), vec![7]);
+
+    let mut empty = List::<u64, U64>::empty();
+    empty.dedup().unwrap();
+    assert!(empty.is_empty());
+}
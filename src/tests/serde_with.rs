@@ -0,0 +1,90 @@
+use crate::{List, Vector};
+use serde::{Deserialize, Serialize};
+use typenum::{U4, U8};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Bytes {
+    #[serde(with = "crate::serde::bytes")]
+    list: List<u8, U8>,
+    #[serde(with = "crate::serde::bytes")]
+    vector: Vector<u8, U4>,
+}
+
+fn bytes(list: &[u8], vector: [u8; 4]) -> Bytes {
+    Bytes {
+        list: List::new(list.to_vec()).unwrap(),
+        vector: Vector::new(vector.to_vec()).unwrap(),
+    }
+}
+
+#[test]
+fn bytes_binary_roundtrip() {
+    for list in [&[][..], &[1, 2, 3], &[0xff; 8]] {
+        let value = bytes(list, [4, 5, 6, 7]);
+        let encoded = bincode::serialize(&value).unwrap();
+        // Each byte string is a u64 length prefix followed by the raw bytes.
+        assert_eq!(encoded.len(), 8 + list.len() + 8 + 4);
+        assert_eq!(bincode::deserialize::<Bytes>(&encoded).unwrap(), value);
+    }
+}
+
+#[test]
+fn bytes_binary_too_long() {
+    let encoded = bincode::serialize(&(vec![0u8; 9], vec![0u8; 4])).unwrap();
+    assert!(bincode::deserialize::<Bytes>(&encoded).is_err());
+
+    let encoded = bincode::serialize(&(vec![0u8; 8], vec![0u8; 3])).unwrap();
+    assert!(bincode::deserialize::<Bytes>(&encoded).is_err());
+}
+
+#[test]
+fn bytes_human_readable_is_sequence() {
+    let value = bytes(&[1, 2], [0, 0, 0, 255]);
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(json, r#"{"list":[1,2],"vector":[0,0,0,255]}"#);
+    assert_eq!(serde_json::from_str::<Bytes>(&json).unwrap(), value);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Hex {
+    #[serde(with = "crate::serde::hex")]
+    list: List<u8, U8>,
+    #[serde(with = "crate::serde::hex")]
+    vector: Vector<u8, U4>,
+}
+
+#[test]
+fn hex_roundtrip() {
+    for (list, json) in [
+        (vec![], r#"{"list":"0x","vector":"0x0102abff"}"#),
+        (
+            vec![0xff; 8],
+            r#"{"list":"0xffffffffffffffff","vector":"0x0102abff"}"#,
+        ),
+    ] {
+        let value = Hex {
+            list: List::new(list).unwrap(),
+            vector: Vector::new(vec![1, 2, 0xab, 0xff]).unwrap(),
+        };
+        assert_eq!(serde_json::to_string(&value).unwrap(), json);
+        assert_eq!(serde_json::from_str::<Hex>(json).unwrap(), value);
+    }
+}
+
+#[test]
+fn hex_invalid() {
+    for json in [
+        // Too long.
+        r#"{"list":"0x000000000000000000","vector":"0x00000000"}"#,
+        // Wrong vector length.
+        r#"{"list":"0x","vector":"0x000000"}"#,
+        // Missing prefix.
+        r#"{"list":"00","vector":"0x00000000"}"#,
+        // Not hex.
+        r#"{"list":"0xzz","vector":"0x00000000"}"#,
+        // Odd number of digits.
+        r#"{"list":"0x0","vector":"0x00000000"}"#,
+    ] {
+        assert!(serde_json::from_str::<Hex>(json).is_err(), "{json}");
+    }
+}
@@ -0,0 +1,51 @@
+use crate::tree::TreeStats;
+use crate::List;
+#[cfg(feature = "debug")]
+use tree_hash::{Hash256, TreeHash};
+#[cfg(feature = "debug")]
+use typenum::U8;
+
+#[test]
+fn structure_stats_repeat() {
+    // Repeated subtrees are shared, so each level has a single distinct node.
+    let list = List::<u64, typenum::U64>::repeat(7, 64).unwrap();
+    let stats = list.structure_stats();
+    assert_eq!(
+        stats,
+        TreeStats {
+            nodes: 4,
+            leaves: 0,
+            packed_leaves: 1,
+            zeros: 0,
+            shared_references: 4,
+        }
+    );
+    assert_eq!(stats.distinct_nodes(), 5);
+
+    // A list built element by element shares nothing.
+    let list = List::<u64, typenum::U64>::repeat_slow(7, 64).unwrap();
+    assert_eq!(list.structure_stats().packed_leaves, 16);
+    assert_eq!(list.structure_stats().shared_references, 0);
+}
+
+#[cfg(feature = "debug")]
+#[test]
+fn render_ascii() {
+    let list = List::<u64, U8>::new(vec![1, 2, 3, 4, 5]).unwrap();
+    assert_eq!(
+        list.render_ascii(usize::MAX),
+        "Node unhashed\n  PackedLeaf [1, 2, 3, 4]\n  PackedLeaf [5]\n"
+    );
+
+    list.tree_hash_root();
+    let rendered = list.render_ascii(0);
+    let hash = list.interface.backing.tree.tree_hash();
+    let expected = format!(
+        "Node {:02x}{:02x}{:02x}{:02x}\n  ...\n  ...\n",
+        hash[0], hash[1], hash[2], hash[3]
+    );
+    assert_eq!(rendered, expected);
+
+    let list = List::<Hash256, U8>::new(vec![Hash256::ZERO]).unwrap();
+    assert!(list.render_ascii(3).contains("Zero(2)"));
+}
@@ -0,0 +1,353 @@
+use crate::update_map::{DenseUpdateMap, MaxMap};
+use crate::{Cow, Error, List, UpdateMap, Vector};
+use ssz::Encode;
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+use tree_hash::TreeHash;
+use typenum::U64;
+use vec_map::VecMap;
+
+#[test]
+fn dense_update_map_basic() {
+    let mut map = DenseUpdateMap::default();
+    assert!(map.is_empty());
+    assert_eq!(map.max_index(), None);
+
+    assert_eq!(map.insert(3, 30u64), None);
+    assert_eq!(map.insert(3, 31), Some(30));
+    assert_eq!(map.insert(0, 0), None);
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.max_index(), Some(3));
+    assert_eq!(map.get(3), Some(&31));
+    assert_eq!(map.get(2), None);
+    assert_eq!(map.get(100), None);
+
+    // Copy on write only happens for vacant slots.
+    *map.get_mut_with(1, |_| Some(10)).unwrap() += 1;
+    *map.get_mut_with(1, |_| panic!("value already present"))
+        .unwrap() += 1;
+    assert_eq!(map.get(1), Some(&12));
+    assert_eq!(map.get_mut_with(10, |_| None), None);
+    assert_eq!(map.max_index(), Some(3));
+
+    let mut seen = vec![];
+    map.for_each_range(1, 100, |k, v| {
+        seen.push((k, *v));
+        ControlFlow::Continue(Ok::<_, ()>(()))
+    })
+    .unwrap();
+    assert_eq!(seen, vec![(1, 12), (3, 31)]);
+}
+
+#[test]
+fn dense_update_map_unused_cow() {
+    let mut map = DenseUpdateMap::<u64>::default();
+    map.insert(0, 0);
+
+    // Taking a cow without mutating it must not create an update.
+    let value = 5;
+    assert_eq!(*map.get_cow_with(7, |_| Some(&value)).unwrap(), 5);
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.max_index(), Some(0));
+
+    *map.get_cow_with(7, |_| Some(&value))
+        .unwrap()
+        .into_mut()
+        .unwrap() += 1;
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(7), Some(&6));
+    assert_eq!(map.max_index(), Some(7));
+}
+
+#[test]
+fn dense_update_map_overwrite_list() {
+    let mut list = List::<u64, U64, DenseUpdateMap<u64>>::try_from_iter(0..64).unwrap();
+    for i in 0..64 {
+        *list.get_mut(i).unwrap() *= 2;
+    }
+    list.get_cow(5).unwrap().make_mut().unwrap();
+    list.apply_updates().unwrap();
+
+    let expected = List::<u64, U64>::try_from_iter((0..64).map(|i| 2 * i)).unwrap();
+    assert_eq!(list.to_vec(), expected.to_vec());
+    assert_eq!(
+        list.tree_hash_root_cached(),
+        expected.tree_hash_root_cached()
+    );
+}
+
+#[test]
+fn btree_map_sparse_high_index() {
+    type N = typenum::U1048576;
+    let mut list = List::<u64, N, BTreeMap<usize, u64>>::repeat(0, 1_000_000).unwrap();
+    let mut expected = List::<u64, N>::repeat(0, 1_000_000).unwrap();
+
+    *list.get_mut(999_999).unwrap() = 1;
+    *expected.get_mut(999_999).unwrap() = 1;
+    assert_eq!(list.interface.updates.len(), 1);
+
+    list.apply_updates().unwrap();
+    expected.apply_updates().unwrap();
+    assert_eq!(list.tree_hash_root(), expected.tree_hash_root());
+    assert_eq!(list.as_ssz_bytes(), expected.as_ssz_bytes());
+}
+
+fn check_remove_retain<M: UpdateMap<u64>>() {
+    let mut map = M::default();
+    for k in [1, 4, 7, 9] {
+        map.insert(k, k as u64 * 10);
+    }
+
+    assert_eq!(map.remove(9), Some(90));
+    assert_eq!(map.remove(9), None);
+    assert_eq!(map.remove(100), None);
+    assert_eq!(map.max_index(), Some(7));
+    assert_eq!(map.len(), 3);
+
+    map.retain(|k, _| k != 7);
+    assert_eq!(map.max_index(), Some(4));
+    assert_eq!(map.get(7), None);
+
+    map.retain(|_, v| *v < 20);
+    assert_eq!(map.max_index(), Some(1));
+    assert_eq!(map.get(1), Some(&10));
+    assert_eq!(map.len(), 1);
+
+    map.retain(|_, _| false);
+    assert!(map.is_empty());
+    assert_eq!(map.max_index(), None);
+
+    // The max index must track inserts after removals.
+    map.insert(3, 30);
+    assert_eq!(map.max_index(), Some(3));
+}
+
+#[test]
+fn remove_retain_all_maps() {
+    check_remove_retain::<BTreeMap<usize, u64>>();
+    check_remove_retain::<VecMap<u64>>();
+    check_remove_retain::<MaxMap<VecMap<u64>>>();
+    check_remove_retain::<MaxMap<BTreeMap<usize, u64>>>();
+    check_remove_retain::<DenseUpdateMap<u64>>();
+}
+
+#[test]
+fn rollback_speculative_update() {
+    let mut list = List::<u64, U64>::try_from_iter(0..16).unwrap();
+    let orig_root = list.tree_hash_root();
+
+    *list.get_mut(3).unwrap() = 100;
+    list.push(16).unwrap();
+    assert_eq!(list.len(), 17);
+
+    assert_eq!(list.interface.updates.remove(16), Some(16));
+    assert_eq!(list.interface.updates.remove(3), Some(100));
+    assert_eq!(list.len(), 16);
+    assert!(!list.has_pending_updates());
+    assert_eq!(list.tree_hash_root(), orig_root);
+}
+
+#[test]
+fn discard_and_retain_pending() {
+    let mut list = List::<u64, U64>::try_from_iter(0..16).unwrap();
+    let orig_root = list.tree_hash_root();
+
+    *list.get_mut(3).unwrap() = 100;
+    list.push(16).unwrap();
+    list.push(17).unwrap();
+
+    // Discarding a push before the last would leave a hole.
+    assert_eq!(
+        list.discard_pending(16),
+        Err(Error::PendingUpdateHole { index: 16, len: 18 })
+    );
+    assert_eq!(
+        list.retain_pending(|i, _| i != 16),
+        Err(Error::PendingUpdateHole { index: 16, len: 18 })
+    );
+    assert_eq!(list.len(), 18);
+
+    assert_eq!(list.discard_pending(17), Ok(Some(17)));
+    assert_eq!(list.discard_pending(17), Ok(None));
+    assert_eq!(list.len(), 17);
+
+    list.retain_pending(|i, _| i < 16).unwrap();
+    assert_eq!(list.len(), 16);
+    assert_eq!(list.discard_pending(3), Ok(Some(100)));
+    assert!(!list.has_pending_updates());
+    assert_eq!(list.tree_hash_root(), orig_root);
+
+    let mut vector = Vector::<u64, U64>::try_from_iter(0..64).unwrap();
+    *vector.get_mut(0).unwrap() = 10;
+    *vector.get_mut(63).unwrap() = 10;
+    vector.retain_pending(|i, _| i == 0).unwrap();
+    assert_eq!(vector.get(0), Some(&10));
+    assert_eq!(vector.get(63), Some(&63));
+}
+
+fn check_drain<M: UpdateMap<u64>>() {
+    let mut map = M::default();
+    for k in [9, 1, 4, 7] {
+        map.insert(k, k as u64 * 10);
+    }
+
+    let drained = map.drain().collect::<Vec<_>>();
+    assert_eq!(drained, vec![(1, 10), (4, 40), (7, 70), (9, 90)]);
+    assert!(map.is_empty());
+    assert_eq!(map.max_index(), None);
+
+    map.insert(2, 20);
+    assert_eq!(map.max_index(), Some(2));
+    assert_eq!(map.drain().collect::<Vec<_>>(), vec![(2, 20)]);
+}
+
+/// Map implementing only the required `UpdateMap` methods, to exercise the default methods.
+#[derive(Default, Clone)]
+struct RequiredOnly(BTreeMap<usize, u64>);
+
+impl UpdateMap<u64> for RequiredOnly {
+    fn get(&self, k: usize) -> Option<&u64> {
+        UpdateMap::get(&self.0, k)
+    }
+
+    fn get_mut_with<F>(&mut self, k: usize, f: F) -> Option<&mut u64>
+    where
+        F: FnOnce(usize) -> Option<u64>,
+    {
+        self.0.get_mut_with(k, f)
+    }
+
+    fn get_cow_with<'a, F>(&'a mut self, k: usize, f: F) -> Option<Cow<'a, u64>>
+    where
+        F: FnOnce(usize) -> Option<&'a u64>,
+    {
+        self.0.get_cow_with(k, f)
+    }
+
+    fn insert(&mut self, k: usize, value: u64) -> Option<u64> {
+        UpdateMap::insert(&mut self.0, k, value)
+    }
+
+    fn remove(&mut self, k: usize) -> Option<u64> {
+        UpdateMap::remove(&mut self.0, k)
+    }
+
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(usize, &u64) -> bool,
+    {
+        UpdateMap::retain(&mut self.0, f)
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut u64)> {
+        UpdateMap::iter_mut(&mut self.0)
+    }
+
+    fn for_each_range<F, E>(&self, start: usize, end: usize, f: F) -> Result<(), E>
+    where
+        F: FnMut(usize, &u64) -> ControlFlow<(), Result<(), E>>,
+    {
+        self.0.for_each_range(start, end, f)
+    }
+
+    fn max_index(&self) -> Option<usize> {
+        self.0.max_index()
+    }
+
+    fn len(&self) -> usize {
+        UpdateMap::len(&self.0)
+    }
+}
+
+#[test]
+fn drain_all_maps() {
+    check_drain::<BTreeMap<usize, u64>>();
+    check_drain::<VecMap<u64>>();
+    check_drain::<MaxMap<VecMap<u64>>>();
+    check_drain::<MaxMap<BTreeMap<usize, u64>>>();
+    check_drain::<DenseUpdateMap<u64>>();
+    check_drain::<RequiredOnly>();
+}
+
+#[test]
+fn pending_indices() {
+    let mut list = List::<u64, U64>::try_from_iter(0..16).unwrap();
+    assert_eq!(list.pending_len(), 0);
+    assert_eq!(list.pending_indices().next(), None);
+
+    *list.get_mut(10).unwrap() = 1;
+    *list.get_mut(2).unwrap() = 1;
+    list.push(16).unwrap();
+    assert_eq!(list.pending_len(), 3);
+    assert_eq!(list.pending_indices().collect::<Vec<_>>(), vec![2, 10, 16]);
+
+    list.apply_updates().unwrap();
+    assert_eq!(list.pending_len(), 0);
+
+    let mut vector = Vector::<u64, U64>::try_from_iter(0..64).unwrap();
+    *vector.get_mut(63).unwrap() = 0;
+    assert_eq!(vector.pending_indices().collect::<Vec<_>>(), vec![63]);
+    assert_eq!(vector.pending_len(), 1);
+}
+
+#[test]
+fn default_keys() {
+    let mut map = RequiredOnly::default();
+    assert_eq!(map.keys().next(), None);
+
+    for k in [9, 0, 4] {
+        map.insert(k, 1);
+    }
+    assert_eq!(map.keys().collect::<Vec<_>>(), vec![0, 4, 9]);
+}
+
+#[test]
+fn reserve_updates() {
+    let mut map = VecMap::<u64>::new();
+    UpdateMap::reserve(&mut map, 100);
+    assert!(map.capacity() >= 100);
+    UpdateMap::insert(&mut map, 9, 0);
+    UpdateMap::reserve(&mut map, 100);
+    assert!(map.capacity() >= 110);
+
+    let mut list = List::<u64, U64>::try_from_iter(0..64).unwrap();
+    list.reserve_updates(64);
+    for i in 0..64 {
+        *list.get_mut(i).unwrap() += 1;
+    }
+    list.apply_updates().unwrap();
+    assert_eq!(list.to_vec(), (1..=64).collect::<Vec<_>>());
+
+    let mut list = List::<u64, U64, BTreeMap<usize, u64>>::try_from_iter(0..64).unwrap();
+    list.reserve_updates(64);
+    *list.get_mut(63).unwrap() = 0;
+    assert_eq!(list.pending_len(), 1);
+
+    let mut vector = Vector::<u64, U64, DenseUpdateMap<u64>>::try_from_iter(0..64).unwrap();
+    vector.reserve_updates(64);
+    *vector.get_mut(0).unwrap() = 100;
+    assert_eq!(vector.get(0), Some(&100));
+}
+
+#[test]
+fn max_map_tracks_get_mut_with() {
+    let mut map = MaxMap::<VecMap<u64>>::default();
+
+    // Values fetched from the backing list are inserted by `get_mut_with`.
+    *map.get_mut_with(5, |_| Some(50)).unwrap() += 1;
+    assert_eq!(map.max_index(), Some(5));
+    assert_eq!(map.get_mut_with(9, |_| None), None);
+    assert_eq!(map.max_index(), Some(5));
+
+    map.insert(2, 20);
+    assert_eq!(map.max_index(), Some(5));
+    map.remove(5);
+    assert_eq!(map.max_index(), Some(2));
+
+    // A cow which may be mutated counts towards the maximum.
+    let backing = 70;
+    let cow = map.get_cow_with(7, |_| Some(&backing)).unwrap();
+    *cow.into_mut().unwrap() += 1;
+    assert_eq!(map.get(7), Some(&71));
+    assert_eq!(map.max_index(), Some(7));
+}
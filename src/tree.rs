@@ -1,12 +1,24 @@
-use crate::utils::{arb_arc, arb_rwlock, opt_hash, opt_packing_depth, opt_packing_factor, Length};
+use crate::builder::Builder;
+use crate::utils::{
+    arb_arc, arb_rwlock, join, opt_hash, opt_packing_depth, opt_packing_factor, Length,
+};
 use crate::{Arc, Error, Leaf, PackedLeaf, UpdateMap, Value};
 use arbitrary::Arbitrary;
 use educe::Educe;
 use ethereum_hashing::{hash32_concat, ZERO_HASHES};
 use parking_lot::RwLock;
-use std::collections::BTreeMap;
-use std::ops::ControlFlow;
-use tree_hash::Hash256;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::{ControlFlow, Range};
+use tree_hash::{Hash256, BYTES_PER_CHUNK};
+
+/// Minimum number of updates to a subtree for `with_updated_leaves_par` to update its children
+/// in parallel.
+pub const PARALLEL_UPDATES_THRESHOLD: usize = 256;
+
+/// Minimum depth of a subtree for its children to be hashed in parallel.
+///
+/// Below this depth the overhead of `rayon::join` outweighs the cost of hashing.
+pub const PARALLEL_HASHING_MIN_DEPTH: usize = 8;
 
 #[derive(Debug, Educe, Arbitrary)]
 #[educe(PartialEq(bound(T: Value)), Hash)]
@@ -102,6 +114,54 @@ impl<T: Value> Tree<T> {
         }
     }
 
+    /// Append the values at each of the sorted `indices` to `values`, paired with their indices.
+    ///
+    /// The tree is descended once for all of the indices, so lookups of nearby indices share the
+    /// nodes on their common path. Indices without a value are skipped.
+    pub fn get_many_recursive<'a>(
+        &'a self,
+        indices: &[usize],
+        prefix: usize,
+        depth: usize,
+        packing_depth: usize,
+        values: &mut Vec<(usize, &'a T)>,
+    ) {
+        if indices.is_empty() {
+            return;
+        }
+        match self {
+            Self::Leaf(Leaf { value, .. }) if depth == 0 => {
+                values.extend(indices.iter().map(|&index| (index, &**value)));
+            }
+            Self::PackedLeaf(PackedLeaf { values: leaf, .. }) if depth == 0 => {
+                let packing_factor = T::tree_hash_packing_factor();
+                values.extend(indices.iter().filter_map(|&index| {
+                    leaf.get(index % packing_factor).map(|value| (index, value))
+                }));
+            }
+            Self::Node { left, right, .. } if depth > 0 => {
+                let new_depth = depth - 1;
+                let right_prefix = prefix | (1 << (new_depth + packing_depth));
+                let split = indices.partition_point(|&index| index < right_prefix);
+                left.get_many_recursive(
+                    &indices[..split],
+                    prefix,
+                    new_depth,
+                    packing_depth,
+                    values,
+                );
+                right.get_many_recursive(
+                    &indices[split..],
+                    right_prefix,
+                    new_depth,
+                    packing_depth,
+                    values,
+                );
+            }
+            _ => (),
+        }
+    }
+
     /// Create a new tree where the `index`th leaf is set to `new_value`.
     ///
     /// NOTE: callers are responsible for bounds-checking `index` before calling this function.
@@ -247,6 +307,310 @@ impl<T: Value> Tree<T> {
             Self::Zero(_) => 0,
         }
     }
+
+    /// Count the nodes whose hashes will be computed by the next call to `tree_hash`.
+    ///
+    /// Subtrees with a cached hash are not descended into, because hashing doesn't visit them.
+    pub fn dirty_node_count(&self) -> usize {
+        match self {
+            Self::Leaf(Leaf { hash, .. }) | Self::PackedLeaf(PackedLeaf { hash, .. }) => {
+                usize::from(hash.read().is_zero())
+            }
+            Self::Node { hash, left, right } => {
+                if hash.read().is_zero() {
+                    1 + left.dirty_node_count() + right.dirty_node_count()
+                } else {
+                    0
+                }
+            }
+            Self::Zero(_) => 0,
+        }
+    }
+
+    /// Compute the depth of this tree by descending its left-most branch.
+    ///
+    /// The depth excludes the packing depth, i.e. leaves and packed leaves are at depth 0.
+    pub fn compute_depth(&self) -> usize {
+        match self {
+            Self::Leaf(_) | Self::PackedLeaf(_) => 0,
+            Self::Node { left, .. } => left.compute_depth() + 1,
+            Self::Zero(depth) => *depth,
+        }
+    }
+
+    /// Copy this tree without sharing any nodes or leaf values with the original.
+    ///
+    /// Cached hashes are copied too. Values are copied with `T::clone`, so data shared within
+    /// the values themselves (e.g. nested lists) remains shared.
+    pub fn deep_clone(&self) -> Arc<Self> {
+        match self {
+            Self::Node { hash, left, right } => {
+                Self::node(left.deep_clone(), right.deep_clone(), *hash.read())
+            }
+            Self::Leaf(Leaf { hash, value }) => {
+                Self::leaf_with_hash(value.as_ref().clone(), *hash.read())
+            }
+            Self::PackedLeaf(leaf) => Arc::new(Self::PackedLeaf(leaf.clone())),
+            Self::Zero(depth) => Self::zero(*depth),
+        }
+    }
+
+    /// Append the values of `tree` to `values`, moving them out of any uniquely owned parts of
+    /// the tree and cloning them from shared parts.
+    pub fn into_values(tree: Arc<Self>, values: &mut Vec<T>) {
+        match Arc::try_unwrap(tree) {
+            Ok(Self::Node { left, right, .. }) => {
+                Self::into_values(left, values);
+                Self::into_values(right, values);
+            }
+            Ok(Self::Leaf(Leaf { value, .. })) => {
+                values.push(Arc::try_unwrap(value).unwrap_or_else(|value| (*value).clone()));
+            }
+            Ok(Self::PackedLeaf(leaf)) => values.extend(leaf.values),
+            Ok(Self::Zero(_)) => (),
+            Err(tree) => tree.clone_values(values),
+        }
+    }
+
+    fn clone_values(&self, values: &mut Vec<T>) {
+        match self {
+            Self::Node { left, right, .. } => {
+                left.clone_values(values);
+                right.clone_values(values);
+            }
+            Self::Leaf(Leaf { value, .. }) => values.push((**value).clone()),
+            Self::PackedLeaf(leaf) => values.extend_from_slice(&leaf.values),
+            Self::Zero(_) => (),
+        }
+    }
+
+    /// Map every value in this tree through `f`, preserving the shape of the tree.
+    ///
+    /// Subtrees that are shared within this tree are mapped once and remain shared in the result.
+    /// Zero subtrees map to zero subtrees of the same depth. Cached hashes are not carried over.
+    ///
+    /// If `T` and `R` have different packing factors the shape of the tree can't be preserved,
+    /// and the mapped values are pushed into a builder for a tree of the adjusted depth instead.
+    pub fn map_leaves<R: Value>(&self, f: &impl Fn(&T) -> R) -> Result<Arc<Tree<R>>, Error> {
+        if opt_packing_factor::<T>() == opt_packing_factor::<R>() {
+            return Ok(self.map_leaves_memo(f, &mut HashMap::new()));
+        }
+        let depth = (self.compute_depth() + opt_packing_depth::<T>().unwrap_or(0))
+            .saturating_sub(opt_packing_depth::<R>().unwrap_or(0));
+        let mut builder = Builder::new(depth, 0)?;
+        self.push_mapped_values(f, &mut builder)?;
+        let (tree, _, _) = builder.finish()?;
+        Ok(tree)
+    }
+
+    fn push_mapped_values<R: Value>(
+        &self,
+        f: &impl Fn(&T) -> R,
+        builder: &mut Builder<R>,
+    ) -> Result<(), Error> {
+        match self {
+            Self::Node { left, right, .. } => {
+                left.push_mapped_values(f, builder)?;
+                right.push_mapped_values(f, builder)
+            }
+            Self::Leaf(Leaf { value, .. }) => builder.push(f(value)),
+            Self::PackedLeaf(leaf) => leaf
+                .values
+                .iter()
+                .try_for_each(|value| builder.push(f(value))),
+            Self::Zero(_) => Ok(()),
+        }
+    }
+
+    fn map_leaves_memo<R: Value>(
+        &self,
+        f: &impl Fn(&T) -> R,
+        memo: &mut HashMap<*const Self, Arc<Tree<R>>>,
+    ) -> Arc<Tree<R>> {
+        let key = self as *const Self;
+        if let Some(mapped) = memo.get(&key) {
+            return mapped.clone();
+        }
+        let mapped = match self {
+            Self::Node { left, right, .. } => Tree::node(
+                left.map_leaves_memo(f, memo),
+                right.map_leaves_memo(f, memo),
+                Hash256::ZERO,
+            ),
+            Self::Leaf(Leaf { value, .. }) => Tree::leaf(f(value)),
+            Self::PackedLeaf(leaf) => Arc::new(Tree::PackedLeaf(PackedLeaf {
+                hash: RwLock::new(Hash256::ZERO),
+                values: leaf.values.iter().map(f).collect(),
+            })),
+            Self::Zero(depth) => Tree::zero(*depth),
+        };
+        memo.insert(key, mapped.clone());
+        mapped
+    }
+
+    /// Call `f` with the tree hash root of every element in this tree, in index order.
+    ///
+    /// Unpacked leaves use (and populate) their cached hash, while packed values are hashed
+    /// individually.
+    pub fn for_each_element_hash(&self, f: &mut impl FnMut(Hash256)) {
+        match self {
+            Self::Node { left, right, .. } => {
+                left.for_each_element_hash(f);
+                right.for_each_element_hash(f);
+            }
+            Self::Leaf(_) => f(self.tree_hash()),
+            Self::PackedLeaf(leaf) => {
+                for value in &leaf.values {
+                    f(value.tree_hash_root());
+                }
+            }
+            Self::Zero(_) => (),
+        }
+    }
+
+    /// Fold over the elements at indices `prefix << packing_depth..len`, skipping zero subtrees.
+    ///
+    /// A subtree is zero if it is a `Zero` node or an internal node whose cached hash is the zero
+    /// hash, meaning all its elements hash to zero. Rather than visiting their elements, `zero`
+    /// is called with the number of elements in such a subtree that lie before `len`. All other
+    /// elements are passed to `leaf` in index order.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fold_subtrees<A>(
+        &self,
+        acc: A,
+        prefix: usize,
+        depth: usize,
+        packing_depth: usize,
+        len: usize,
+        leaf: &mut impl FnMut(A, &T) -> A,
+        zero: &mut impl FnMut(A, usize) -> A,
+    ) -> A {
+        let start = prefix << packing_depth;
+        if start >= len {
+            return acc;
+        }
+        let end = std::cmp::min((prefix + (1 << depth)) << packing_depth, len);
+        match self {
+            Self::Zero(_) => zero(acc, end - start),
+            Self::Node { hash, .. }
+                if depth > 0 && *hash.read() == Hash256::from(ZERO_HASHES[depth]) =>
+            {
+                zero(acc, end - start)
+            }
+            Self::Node { left, right, .. } if depth > 0 => {
+                let new_depth = depth - 1;
+                let acc =
+                    left.fold_subtrees(acc, prefix, new_depth, packing_depth, len, leaf, zero);
+                right.fold_subtrees(
+                    acc,
+                    prefix + (1 << new_depth),
+                    new_depth,
+                    packing_depth,
+                    len,
+                    leaf,
+                    zero,
+                )
+            }
+            Self::Leaf(Leaf { value, .. }) => leaf(acc, value),
+            Self::PackedLeaf(PackedLeaf { values, .. }) => values
+                .iter()
+                .take(end - start)
+                .fold(acc, |acc, value| leaf(acc, value)),
+            Self::Node { .. } => acc,
+        }
+    }
+
+    /// Append the SSZ encoding of every element in this tree to `buf`.
+    ///
+    /// The SSZ encoding of `T` must be the same as its packed encoding (see
+    /// `ssz_is_packed_encoding`). Packed leaves with a cached hash are copied into `buf` in one
+    /// go, because the hash of a packed leaf is the concatenation of its values' encodings.
+    pub fn ssz_append_packed(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::Node { left, right, .. } => {
+                left.ssz_append_packed(buf);
+                right.ssz_append_packed(buf);
+            }
+            Self::PackedLeaf(leaf) => {
+                let hash = *leaf.hash.read();
+                if !hash.is_zero() {
+                    let value_len = BYTES_PER_CHUNK / T::tree_hash_packing_factor();
+                    buf.extend_from_slice(&hash.as_slice()[..leaf.values.len() * value_len]);
+                } else {
+                    for value in &leaf.values {
+                        value.ssz_append(buf);
+                    }
+                }
+            }
+            Self::Leaf(leaf) => leaf.value.ssz_append(buf),
+            Self::Zero(_) => (),
+        }
+    }
+
+    /// Return the hash cached in this node, if it has been computed.
+    fn cached_hash(&self) -> Option<Hash256> {
+        let hash = match self {
+            Self::Leaf(Leaf { hash, .. })
+            | Self::PackedLeaf(PackedLeaf { hash, .. })
+            | Self::Node { hash, .. } => *hash.read(),
+            Self::Zero(_) => return None,
+        };
+        Some(hash).filter(|hash| !hash.is_zero())
+    }
+
+    /// Collect the ranges of element indices at which `self` and `other` are not shared.
+    ///
+    /// Subtrees that are pointer-equal, have equal cached hashes, or are both zero are skipped
+    /// without being visited. Adjacent ranges are merged. Both trees must have the given
+    /// `depth`, and `prefix` is the index of the first leaf of `self`, in units of (packed)
+    /// leaves.
+    pub fn unshared_ranges(
+        &self,
+        other: &Self,
+        prefix: usize,
+        depth: usize,
+        packing_depth: usize,
+        ranges: &mut Vec<Range<usize>>,
+    ) {
+        if let (Some(hash), Some(other_hash)) = (self.cached_hash(), other.cached_hash()) {
+            if hash == other_hash {
+                return;
+            }
+        }
+        match (self, other) {
+            (Self::Zero(_), Self::Zero(_)) => (),
+            (
+                Self::Node { left, right, .. },
+                Self::Node {
+                    left: other_left,
+                    right: other_right,
+                    ..
+                },
+            ) if depth > 0 => {
+                let new_depth = depth - 1;
+                if !Arc::ptr_eq(left, other_left) {
+                    left.unshared_ranges(other_left, prefix, new_depth, packing_depth, ranges);
+                }
+                if !Arc::ptr_eq(right, other_right) {
+                    right.unshared_ranges(
+                        other_right,
+                        prefix + (1 << new_depth),
+                        new_depth,
+                        packing_depth,
+                        ranges,
+                    );
+                }
+            }
+            _ => {
+                let start = prefix << packing_depth;
+                let end = (prefix + (1 << depth)) << packing_depth;
+                match ranges.last_mut() {
+                    Some(last) if last.end == start => last.end = end,
+                    _ => ranges.push(start..end),
+                }
+            }
+        }
+    }
 }
 
 pub enum RebaseAction<'a, T> {
@@ -260,18 +624,161 @@ pub enum RebaseAction<'a, T> {
     EqualReplace(&'a Arc<T>),
 }
 
+/// Statistics describing the effect of a rebase.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RebaseStats {
+    /// Number of subtrees of the original tree that were replaced by a subtree from the base.
+    pub nodes_replaced: usize,
+    /// Number of nodes of the original tree that differ from the base and were kept.
+    pub nodes_kept: usize,
+    /// Number of leaves (packed or unpacked) that were compared by value.
+    pub leaves_compared: usize,
+}
+
+#[cfg(feature = "debug")]
+impl<T: Value> Tree<T> {
+    /// Render the tree as an indented outline, one node per line, up to `max_depth` levels
+    /// below the root.
+    ///
+    /// Internal nodes show the first 4 bytes of their cached hash (or `unhashed`), and leaves
+    /// show their values. Subtrees below `max_depth` are elided as `...`.
+    pub fn render_ascii(&self, max_depth: usize) -> String {
+        let mut out = String::new();
+        self.render_ascii_inner(0, max_depth, &mut out);
+        out
+    }
+
+    fn render_ascii_inner(&self, level: usize, max_depth: usize, out: &mut String) {
+        use std::fmt::Write;
+
+        let indent = "  ".repeat(level);
+        if level > max_depth {
+            let _ = writeln!(out, "{indent}...");
+            return;
+        }
+        match self {
+            Self::Node { hash, left, right } => {
+                let hash = *hash.read();
+                if hash.is_zero() {
+                    let _ = writeln!(out, "{indent}Node unhashed");
+                } else {
+                    let _ = writeln!(out, "{indent}Node {}", hex_prefix(&hash));
+                }
+                left.render_ascii_inner(level + 1, max_depth, out);
+                right.render_ascii_inner(level + 1, max_depth, out);
+            }
+            Self::Leaf(Leaf { value, .. }) => {
+                let _ = writeln!(out, "{indent}Leaf {value:?}");
+            }
+            Self::PackedLeaf(PackedLeaf { values, .. }) => {
+                let _ = writeln!(out, "{indent}PackedLeaf {values:?}");
+            }
+            Self::Zero(depth) => {
+                let _ = writeln!(out, "{indent}Zero({depth})");
+            }
+        }
+    }
+}
+
+/// Format the first 4 bytes of `hash` as hex.
+#[cfg(feature = "debug")]
+fn hex_prefix(hash: &Hash256) -> String {
+    hash.as_slice()[..4]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Counts of the distinct nodes making up a tree, as computed by `Tree::structure_stats`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TreeStats {
+    /// Number of internal nodes.
+    pub nodes: usize,
+    /// Number of unpacked leaves.
+    pub leaves: usize,
+    /// Number of packed leaves.
+    pub packed_leaves: usize,
+    /// Number of zero subtrees.
+    pub zeros: usize,
+    /// Number of references to nodes which had already been counted, i.e. shared subtrees.
+    pub shared_references: usize,
+}
+
+impl TreeStats {
+    /// Total number of distinct nodes (of any kind) in the tree.
+    pub fn distinct_nodes(&self) -> usize {
+        self.nodes + self.leaves + self.packed_leaves + self.zeros
+    }
+}
+
+impl<T: Value> Tree<T> {
+    /// Count the distinct nodes of each kind in this tree.
+    ///
+    /// Nodes are identified by address, so a subtree referenced from several places is counted
+    /// once, and its other references are counted in `shared_references`.
+    pub fn structure_stats(&self) -> TreeStats {
+        let mut stats = TreeStats::default();
+        let mut visited = HashSet::new();
+        self.structure_stats_inner(&mut stats, &mut visited);
+        stats
+    }
+
+    fn structure_stats_inner(&self, stats: &mut TreeStats, visited: &mut HashSet<*const Self>) {
+        if !visited.insert(self as *const Self) {
+            stats.shared_references += 1;
+            return;
+        }
+        match self {
+            Self::Node { left, right, .. } => {
+                stats.nodes += 1;
+                left.structure_stats_inner(stats, visited);
+                right.structure_stats_inner(stats, visited);
+            }
+            Self::Leaf(_) => stats.leaves += 1,
+            Self::PackedLeaf(_) => stats.packed_leaves += 1,
+            Self::Zero(_) => stats.zeros += 1,
+        }
+    }
+}
+
 impl<T: Value> Tree<T> {
     pub fn rebase_on<'a>(
         orig: &'a Arc<Self>,
         base: &'a Arc<Self>,
         lengths: Option<(Length, Length)>,
         full_depth: usize,
+    ) -> Result<RebaseAction<'a, Self>, Error> {
+        Self::rebase_on_with_stats(orig, base, lengths, full_depth, &mut RebaseStats::default())
+    }
+
+    /// Rebase `orig` on `base`, accumulating statistics about the rebase in `stats`.
+    pub fn rebase_on_with_stats<'a>(
+        orig: &'a Arc<Self>,
+        base: &'a Arc<Self>,
+        lengths: Option<(Length, Length)>,
+        full_depth: usize,
+        stats: &mut RebaseStats,
+    ) -> Result<RebaseAction<'a, Self>, Error> {
+        let action = Self::rebase_on_inner(orig, base, lengths, full_depth, stats)?;
+        if let RebaseAction::NotEqualNoop | RebaseAction::NotEqualReplace(_) = action {
+            stats.nodes_kept += 1;
+        }
+        Ok(action)
+    }
+
+    fn rebase_on_inner<'a>(
+        orig: &'a Arc<Self>,
+        base: &'a Arc<Self>,
+        lengths: Option<(Length, Length)>,
+        full_depth: usize,
+        stats: &mut RebaseStats,
     ) -> Result<RebaseAction<'a, Self>, Error> {
         if Arc::ptr_eq(orig, base) {
             return Ok(RebaseAction::EqualNoop);
         }
         match (&**orig, &**base) {
             (Self::Leaf(l1), Self::Leaf(l2)) => {
+                stats.leaves_compared += 1;
                 if l1.value == l2.value {
                     Ok(RebaseAction::EqualReplace(base))
                 } else {
@@ -279,6 +786,7 @@ impl<T: Value> Tree<T> {
                 }
             }
             (Self::PackedLeaf(l1), Self::PackedLeaf(l2)) => {
+                stats.leaves_compared += 1;
                 if l1.values == l2.values {
                     Ok(RebaseAction::EqualReplace(base))
                 } else {
@@ -333,8 +841,10 @@ impl<T: Value> Tree<T> {
                     })
                     .unzip();
 
-                let left_action = Tree::rebase_on(l1, l2, left_lengths, new_full_depth)?;
-                let right_action = Tree::rebase_on(r1, r2, right_lengths, new_full_depth)?;
+                let left_action =
+                    Tree::rebase_on_with_stats(l1, l2, left_lengths, new_full_depth, stats)?;
+                let right_action =
+                    Tree::rebase_on_with_stats(r1, r2, right_lengths, new_full_depth, stats)?;
 
                 match (left_action, right_action) {
                     (NotEqualNoop, NotEqualNoop | EqualNoop) | (EqualNoop, NotEqualNoop) => {
@@ -349,6 +859,7 @@ impl<T: Value> Tree<T> {
                         })))
                     }
                     (NotEqualNoop | EqualNoop, EqualReplace(new_right)) => {
+                        stats.nodes_replaced += 1;
                         Ok(NotEqualReplace(Arc::new(Self::Node {
                             hash: RwLock::new(orig_hash),
                             left: l1.clone(),
@@ -370,6 +881,7 @@ impl<T: Value> Tree<T> {
                         })))
                     }
                     (NotEqualReplace(new_left), EqualReplace(new_right)) => {
+                        stats.nodes_replaced += 1;
                         Ok(NotEqualReplace(Arc::new(Self::Node {
                             hash: RwLock::new(orig_hash),
                             left: new_left,
@@ -377,6 +889,7 @@ impl<T: Value> Tree<T> {
                         })))
                     }
                     (EqualReplace(new_left), NotEqualNoop) => {
+                        stats.nodes_replaced += 1;
                         Ok(NotEqualReplace(Arc::new(Self::Node {
                             hash: RwLock::new(orig_hash),
                             left: new_left.clone(),
@@ -384,6 +897,7 @@ impl<T: Value> Tree<T> {
                         })))
                     }
                     (EqualReplace(new_left), NotEqualReplace(new_right)) => {
+                        stats.nodes_replaced += 1;
                         Ok(NotEqualReplace(Arc::new(Self::Node {
                             hash: RwLock::new(orig_hash),
                             left: new_left.clone(),
@@ -405,7 +919,163 @@ impl<T: Value> Tree<T> {
 }
 
 impl<T: Value + Send + Sync> Tree<T> {
+    /// Parallel version of `with_updated_leaves`.
+    ///
+    /// Subtrees with at least `PARALLEL_UPDATES_THRESHOLD` updates are updated in parallel when
+    /// the updates are split between both children, smaller subtrees are updated sequentially.
+    /// The resulting tree is identical to the one produced by `with_updated_leaves`.
+    pub fn with_updated_leaves_par<U: UpdateMap<T> + Sync>(
+        &self,
+        updates: &U,
+        prefix: usize,
+        depth: usize,
+        hashes: Option<&BTreeMap<(usize, usize), Hash256>>,
+    ) -> Result<Arc<Self>, Error> {
+        match self {
+            Self::Node { left, right, .. } if depth > 0 => {
+                let packing_depth = opt_packing_depth::<T>().unwrap_or(0);
+                let new_depth = depth - 1;
+                let left_prefix = prefix;
+                let right_prefix = prefix | (1 << (new_depth + packing_depth));
+                let right_subtree_end = prefix + (1 << (depth + packing_depth));
+
+                let mut num_left_updates = 0;
+                updates.for_each_range(left_prefix, right_prefix, |_, _| {
+                    num_left_updates += 1;
+                    if num_left_updates < PARALLEL_UPDATES_THRESHOLD {
+                        ControlFlow::Continue(Ok(()))
+                    } else {
+                        ControlFlow::Break(())
+                    }
+                })?;
+                let mut num_right_updates = 0;
+                updates.for_each_range(right_prefix, right_subtree_end, |_, _| {
+                    num_right_updates += 1;
+                    if num_right_updates < PARALLEL_UPDATES_THRESHOLD {
+                        ControlFlow::Continue(Ok(()))
+                    } else {
+                        ControlFlow::Break(())
+                    }
+                })?;
+
+                if num_left_updates + num_right_updates < PARALLEL_UPDATES_THRESHOLD {
+                    return self.with_updated_leaves(updates, prefix, depth, hashes);
+                }
+
+                let hash = opt_hash(hashes, depth, prefix).unwrap_or_default();
+
+                // If only one side has updates, descend into it until the updates split.
+                if num_right_updates == 0 {
+                    let new_left =
+                        left.with_updated_leaves_par(updates, left_prefix, new_depth, hashes)?;
+                    return Ok(Self::node(new_left, right.clone(), hash));
+                }
+                if num_left_updates == 0 {
+                    let new_right =
+                        right.with_updated_leaves_par(updates, right_prefix, new_depth, hashes)?;
+                    return Ok(Self::node(left.clone(), new_right, hash));
+                }
+
+                let (new_left, new_right) = join(
+                    || left.with_updated_leaves_par(updates, left_prefix, new_depth, hashes),
+                    || right.with_updated_leaves_par(updates, right_prefix, new_depth, hashes),
+                );
+                Ok(Self::node(new_left?, new_right?, hash))
+            }
+            Self::Zero(zero_depth) if *zero_depth == depth && depth > 0 => {
+                // Split zero node into a node with left and right and recurse.
+                let hash = opt_hash(hashes, depth, prefix).unwrap_or_default();
+                let new_zero = Self::zero(depth - 1);
+                Self::node(new_zero.clone(), new_zero, hash)
+                    .with_updated_leaves_par(updates, prefix, depth, hashes)
+            }
+            _ => self.with_updated_leaves(updates, prefix, depth, hashes),
+        }
+    }
+
+    /// Compute the hash of the internal node at `index` on `level`, counting levels up from the
+    /// leaves (which are on level 0).
+    ///
+    /// The subtree is hashed if necessary. Return `None` if the position lies outside a tree of
+    /// the given `depth`.
+    pub fn subtree_hash(&self, index: usize, level: usize, depth: usize) -> Option<Hash256> {
+        if level > depth || index >> (depth - level) != 0 {
+            return None;
+        }
+        let mut node = self;
+        let mut current_depth = depth;
+        while current_depth > level {
+            match node {
+                Self::Node { left, right, .. } => {
+                    current_depth -= 1;
+                    node = if (index >> (current_depth - level)) & 1 == 0 {
+                        left
+                    } else {
+                        right
+                    };
+                }
+                Self::Zero(_) => return Some(Hash256::from(ZERO_HASHES[level])),
+                _ => return None,
+            }
+        }
+        Some(node.tree_hash())
+    }
+
+    /// Compute the tree hash without writing any computed hashes back into the tree.
+    ///
+    /// Existing cached hashes are still read. This avoids taking write locks, which is useful
+    /// when many threads hash the same shared tree, but repeated calls are slower than
+    /// `tree_hash` because nothing new is cached.
+    pub fn tree_hash_readonly(&self) -> Hash256 {
+        match self {
+            Self::Leaf(Leaf { hash, value }) => {
+                let existing_hash = *hash.read();
+                if !existing_hash.is_zero() {
+                    existing_hash
+                } else {
+                    value.tree_hash_root()
+                }
+            }
+            Self::PackedLeaf(leaf) => {
+                let existing_hash = *leaf.hash.read();
+                if !existing_hash.is_zero() {
+                    existing_hash
+                } else {
+                    leaf.compute_hash()
+                }
+            }
+            Self::Zero(depth) => Hash256::from(ZERO_HASHES[*depth]),
+            Self::Node { hash, left, right } => {
+                let existing_hash = *hash.read();
+                if !existing_hash.is_zero() {
+                    existing_hash
+                } else {
+                    let (left_hash, right_hash) =
+                        join(|| left.tree_hash_readonly(), || right.tree_hash_readonly());
+                    Hash256::from(hash32_concat(left_hash.as_slice(), right_hash.as_slice()))
+                }
+            }
+        }
+    }
+
+    /// Compute the tree hash, caching the hashes of nodes and leaves in the tree.
+    ///
+    /// This is safe to call concurrently on a shared tree. Threads that find the same hash
+    /// missing may each compute it, but they write the same value, so the cache stays consistent.
     pub fn tree_hash(&self) -> Hash256 {
+        self.tree_hash_inner(true, None)
+    }
+
+    /// Compute the tree hash on the current thread, without using rayon.
+    pub fn tree_hash_sequential(&self) -> Hash256 {
+        self.tree_hash_inner(false, None)
+    }
+
+    /// Compute the tree hash, in parallel if `parallel` is set.
+    ///
+    /// The `depth` of `self` is computed on demand if not provided. Subtrees with a depth less than
+    /// `PARALLEL_HASHING_MIN_DEPTH` are always hashed sequentially.
+    fn tree_hash_inner(&self, parallel: bool, depth: Option<usize>) -> Hash256 {
         match self {
             Self::Leaf(Leaf { hash, value }) => {
                 // FIXME(sproul): upgradeable RwLock?
@@ -438,9 +1108,21 @@ impl<T: Value + Send + Sync> Tree<T> {
                 if !existing_hash.is_zero() {
                     existing_hash
                 } else {
-                    // Parallelism goes brrrr.
-                    let (left_hash, right_hash) =
-                        rayon::join(|| left.tree_hash(), || right.tree_hash());
+                    let depth = depth.unwrap_or_else(|| self.compute_depth());
+                    let child_depth = Some(depth.saturating_sub(1));
+                    let (left_hash, right_hash) = if parallel && depth >= PARALLEL_HASHING_MIN_DEPTH
+                    {
+                        // Parallelism goes brrrr.
+                        join(
+                            || left.tree_hash_inner(true, child_depth),
+                            || right.tree_hash_inner(true, child_depth),
+                        )
+                    } else {
+                        (
+                            left.tree_hash_inner(false, child_depth),
+                            right.tree_hash_inner(false, child_depth),
+                        )
+                    };
                     let tree_hash =
                         Hash256::from(hash32_concat(left_hash.as_slice(), right_hash.as_slice()));
                     *hash.write() = tree_hash;
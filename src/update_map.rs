@@ -1,12 +1,19 @@
-use crate::cow::{BTreeCow, Cow, VecCow};
+use crate::cow::{BTreeCow, Cow, DenseCow, DenseVacantEntry, VecCow};
 use crate::utils::max_btree_index;
 use arbitrary::Arbitrary;
 use std::collections::{btree_map::Entry, BTreeMap};
+use std::convert::Infallible;
 use std::ops::ControlFlow;
 use vec_map::VecMap;
 
 /// Trait for map types which can be used to store intermediate updates before application
 /// to the tree.
+///
+/// The default for `List` and `Vector` is `MaxMap<VecMap<T>>`, which is fast for dense updates
+/// but allocates a slot for every index up to the largest updated index. For sparse updates at
+/// high indices, e.g. a single `get_mut` near the end of a large list, use `BTreeMap<usize, T>`
+/// instead, which only allocates for the updated indices at the cost of O(log n) lookups. A
+/// `HashMap` is not supported because `for_each_range` must visit keys in sorted order.
 pub trait UpdateMap<T>: Default + Clone {
     fn get(&self, k: usize) -> Option<&T>;
 
@@ -21,6 +28,43 @@ pub trait UpdateMap<T>: Default + Clone {
 
     fn insert(&mut self, k: usize, value: T) -> Option<T>;
 
+    /// Remove the update for index `k`, returning it if present.
+    fn remove(&mut self, k: usize) -> Option<T>;
+
+    /// Retain only the updates for which `f` returns `true`.
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(usize, &T) -> bool;
+
+    /// Remove all updates from the map, yielding them in index order.
+    ///
+    /// The default implementation removes the updates one at a time using `keys` and `remove`.
+    fn drain(&mut self) -> impl Iterator<Item = (usize, T)> {
+        let keys = self.keys().collect::<Vec<_>>();
+        keys.into_iter()
+            .filter_map(|k| Some((k, self.remove(k)?)))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Iterate the indices of all updates in index order.
+    ///
+    /// The default implementation collects the indices using `for_each_range`.
+    fn keys(&self) -> impl Iterator<Item = usize> {
+        let mut keys = vec![];
+        if let Some(max_index) = self.max_index() {
+            self.for_each_range(0, max_index + 1, |k, _| {
+                keys.push(k);
+                ControlFlow::Continue(Ok::<_, Infallible>(()))
+            })
+            .unwrap_or_else(|e| match e {});
+        }
+        keys.into_iter()
+    }
+
+    /// Iterate mutable references to all updates in index order.
+    fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)>;
+
     fn for_each_range<F, E>(&self, start: usize, end: usize, f: F) -> Result<(), E>
     where
         F: FnMut(usize, &T) -> ControlFlow<(), Result<(), E>>;
@@ -33,6 +77,14 @@ pub trait UpdateMap<T>: Default + Clone {
     fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Reserve capacity for `additional` more updates, to avoid reallocating during a burst of
+    /// updates.
+    ///
+    /// Maps that allocate a slot per index reserve slots for the `additional` indices above the
+    /// largest updated index. The default implementation does nothing, which suits maps such as
+    /// `BTreeMap` that can't reserve capacity.
+    fn reserve(&mut self, _additional: usize) {}
 }
 
 impl<T: Clone> UpdateMap<T> for BTreeMap<usize, T> {
@@ -77,6 +129,29 @@ impl<T: Clone> UpdateMap<T> for BTreeMap<usize, T> {
         BTreeMap::insert(self, idx, value)
     }
 
+    fn remove(&mut self, idx: usize) -> Option<T> {
+        BTreeMap::remove(self, &idx)
+    }
+
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &T) -> bool,
+    {
+        BTreeMap::retain(self, |idx, value| f(*idx, value))
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = (usize, T)> {
+        std::mem::take(self).into_iter()
+    }
+
+    fn keys(&self) -> impl Iterator<Item = usize> {
+        BTreeMap::keys(self).copied()
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+        BTreeMap::iter_mut(self).map(|(k, value)| (*k, value))
+    }
+
     fn for_each_range<F, E>(&self, start: usize, end: usize, mut f: F) -> Result<(), E>
     where
         F: FnMut(usize, &T) -> ControlFlow<(), Result<(), E>>,
@@ -141,6 +216,29 @@ impl<T: Clone> UpdateMap<T> for VecMap<T> {
         VecMap::insert(self, idx, value)
     }
 
+    fn remove(&mut self, idx: usize) -> Option<T> {
+        VecMap::remove(self, idx)
+    }
+
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &T) -> bool,
+    {
+        VecMap::retain(self, |idx, value| f(idx, value))
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = (usize, T)> {
+        VecMap::drain(self)
+    }
+
+    fn keys(&self) -> impl Iterator<Item = usize> {
+        VecMap::keys(self)
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+        VecMap::iter_mut(self)
+    }
+
     fn for_each_range<F, E>(&self, start: usize, end: usize, mut f: F) -> Result<(), E>
     where
         F: FnMut(usize, &T) -> ControlFlow<(), Result<(), E>>,
@@ -166,8 +264,179 @@ impl<T: Clone> UpdateMap<T> for VecMap<T> {
     fn len(&self) -> usize {
         VecMap::len(self)
     }
+
+    fn reserve(&mut self, additional: usize) {
+        let len = UpdateMap::max_index(self).map_or(0, |index| index + 1);
+        self.reserve_len(len + additional);
+    }
+}
+
+/// Dense update map backed by a `Vec<Option<T>>`.
+///
+/// This map is intended for workloads that overwrite most of a list starting from index 0,
+/// e.g. replacing every element. Lookups and insertions are O(1), and `for_each_range` is a
+/// single scan over a slice rather than a lookup per index. Prefer it to `MaxMap<VecMap<T>>`
+/// when nearly every index below the maximum updated index is written.
+///
+/// It allocates a slot for every index up to the largest updated index, so sparse updates at
+/// high indices are better served by `BTreeMap`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenseUpdateMap<T> {
+    values: Vec<Option<T>>,
+    len: usize,
+}
+
+impl<T> Default for DenseUpdateMap<T> {
+    fn default() -> Self {
+        Self {
+            values: vec![],
+            len: 0,
+        }
+    }
+}
+
+impl<T> DenseUpdateMap<T> {
+    /// Ensure that the slot for `k` exists, returning it.
+    fn slot(&mut self, k: usize) -> &mut Option<T> {
+        if k >= self.values.len() {
+            self.values.resize_with(k + 1, || None);
+        }
+        &mut self.values[k]
+    }
+}
+
+impl<T: Clone> UpdateMap<T> for DenseUpdateMap<T> {
+    fn get(&self, k: usize) -> Option<&T> {
+        self.values.get(k)?.as_ref()
+    }
+
+    fn get_mut_with<F>(&mut self, k: usize, f: F) -> Option<&mut T>
+    where
+        F: FnOnce(usize) -> Option<T>,
+    {
+        if self.get(k).is_none() {
+            // Copy on write.
+            let value = f(k)?;
+            self.len += 1;
+            *self.slot(k) = Some(value);
+        }
+        self.values[k].as_mut()
+    }
+
+    fn get_cow_with<'a, F>(&'a mut self, k: usize, f: F) -> Option<Cow<'a, T>>
+    where
+        F: FnOnce(usize) -> Option<&'a T>,
+        T: 'a,
+    {
+        let cow = if self.get(k).is_some() {
+            DenseCow::Mutable {
+                value: self.values[k].as_mut()?,
+            }
+        } else {
+            let value = f(k)?;
+            self.slot(k);
+            DenseCow::Immutable {
+                value,
+                entry: Some(DenseVacantEntry {
+                    slot: &mut self.values[k],
+                    len: &mut self.len,
+                }),
+            }
+        };
+        Some(Cow::Dense(cow))
+    }
+
+    fn insert(&mut self, k: usize, value: T) -> Option<T> {
+        let prev = self.slot(k).replace(value);
+        if prev.is_none() {
+            self.len += 1;
+        }
+        prev
+    }
+
+    fn remove(&mut self, k: usize) -> Option<T> {
+        let prev = self.values.get_mut(k)?.take();
+        if prev.is_some() {
+            self.len -= 1;
+        }
+        prev
+    }
+
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &T) -> bool,
+    {
+        for (k, slot) in self.values.iter_mut().enumerate() {
+            if slot.as_ref().is_some_and(|value| !f(k, value)) {
+                *slot = None;
+                self.len -= 1;
+            }
+        }
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = (usize, T)> {
+        std::mem::take(self)
+            .values
+            .into_iter()
+            .enumerate()
+            .filter_map(|(k, slot)| Some((k, slot?)))
+    }
+
+    fn keys(&self) -> impl Iterator<Item = usize> {
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(|(k, slot)| slot.as_ref().map(|_| k))
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+        self.values
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(k, slot)| Some((k, slot.as_mut()?)))
+    }
+
+    fn for_each_range<F, E>(&self, start: usize, end: usize, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(usize, &T) -> ControlFlow<(), Result<(), E>>,
+    {
+        let end = std::cmp::min(end, self.values.len());
+        for (key, value) in self
+            .values
+            .get(start..end)
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            if let Some(value) = value {
+                match f(start + key, value) {
+                    ControlFlow::Continue(res) => res?,
+                    ControlFlow::Break(()) => break,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn max_index(&self) -> Option<usize> {
+        // Slots created by unused `get_cow_with` calls may be vacant.
+        self.values.iter().rposition(Option::is_some)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.values.reserve(additional);
+    }
 }
 
+/// Wrapper around an update map which tracks its maximum index, so that `max_index` is O(1).
+///
+/// Invariant: `max_key` is at least the maximum index of the inner map, and is exact for indices
+/// added by `insert`. It may overestimate after `get_cow_with` on an index that is never mutated,
+/// which is harmless as such indices lie within the backing list and don't affect its length.
 #[derive(Debug, Default, Clone, PartialEq, Arbitrary)]
 #[arbitrary(bound = "M: Default")]
 pub struct MaxMap<M> {
@@ -188,7 +457,17 @@ where
     where
         F: FnOnce(usize) -> Option<T>,
     {
-        self.inner.get_mut_with(k, f)
+        // The inner map inserts the value returned by `f`, if any.
+        let mut inserted = false;
+        let value = self.inner.get_mut_with(k, |k| {
+            let value = f(k);
+            inserted = value.is_some();
+            value
+        });
+        if inserted && k > self.max_key {
+            self.max_key = k;
+        }
+        value
     }
 
     fn get_cow_with<'a, F>(&'a mut self, k: usize, f: F) -> Option<Cow<'a, T>>
@@ -196,7 +475,18 @@ where
         F: FnOnce(usize) -> Option<&'a T>,
         T: Clone + 'a,
     {
-        self.inner.get_cow_with(k, f)
+        // The value returned by `f` is inserted if the cow is mutated, which we can't observe, so
+        // assume that it will be.
+        let mut found = false;
+        let cow = self.inner.get_cow_with(k, |k| {
+            let value = f(k);
+            found = value.is_some();
+            value
+        });
+        if found && k > self.max_key {
+            self.max_key = k;
+        }
+        cow
     }
 
     fn insert(&mut self, k: usize, value: T) -> Option<T> {
@@ -206,6 +496,35 @@ where
         self.inner.insert(k, value)
     }
 
+    fn remove(&mut self, k: usize) -> Option<T> {
+        let prev = self.inner.remove(k);
+        if k == self.max_key {
+            self.max_key = self.inner.max_index().unwrap_or(0);
+        }
+        prev
+    }
+
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(usize, &T) -> bool,
+    {
+        self.inner.retain(f);
+        self.max_key = self.inner.max_index().unwrap_or(0);
+    }
+
+    fn drain(&mut self) -> impl Iterator<Item = (usize, T)> {
+        self.max_key = 0;
+        self.inner.drain()
+    }
+
+    fn keys(&self) -> impl Iterator<Item = usize> {
+        self.inner.keys()
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+        self.inner.iter_mut()
+    }
+
     fn for_each_range<F, E>(&self, start: usize, end: usize, f: F) -> Result<(), E>
     where
         F: FnMut(usize, &T) -> ControlFlow<(), Result<(), E>>,
@@ -217,7 +536,16 @@ where
         self.inner.len()
     }
 
+    fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional)
+    }
+
     fn max_index(&self) -> Option<usize> {
+        debug_assert!(
+            self.inner.max_index().unwrap_or(0) <= self.max_key,
+            "MaxMap max_key {} is less than the max index of the inner map",
+            self.max_key
+        );
         Some(self.max_key).filter(|_| !self.inner.is_empty())
     }
 }
@@ -1,8 +1,9 @@
-use crate::{Arc, UpdateMap};
+use crate::{Arc, Error, UpdateMap, MAX_TREE_LENGTH};
 use arbitrary::Arbitrary;
 use parking_lot::RwLock;
+use ssz::Encode;
 use std::collections::BTreeMap;
-use tree_hash::{Hash256, TreeHash, TreeHashType};
+use tree_hash::{Hash256, TreeHash, TreeHashType, BYTES_PER_CHUNK};
 
 /// Type to abstract over whether `T` is wrapped in an `Arc` or not.
 #[derive(Debug)]
@@ -71,6 +72,17 @@ pub fn opt_packing_factor<T: TreeHash>() -> Option<usize> {
     }
 }
 
+/// Check whether the SSZ encoding of `T` is the same as its packed tree hash encoding.
+///
+/// This is true for the basic types (integers and booleans), for which the cached hash of a
+/// `PackedLeaf` is the concatenation of the SSZ encodings of its values.
+pub fn ssz_is_packed_encoding<T: Encode + TreeHash>() -> bool {
+    opt_packing_factor::<T>().is_some_and(|packing_factor| {
+        <T as Encode>::is_ssz_fixed_len()
+            && <T as Encode>::ssz_fixed_len() * packing_factor == BYTES_PER_CHUNK
+    })
+}
+
 /// Compute the depth in a tree at which to start packing values into a `PackedLeaf`.
 pub fn opt_packing_depth<T: TreeHash>() -> Option<usize> {
     let packing_factor = opt_packing_factor::<T>()?;
@@ -83,12 +95,50 @@ pub fn max_btree_index<T>(map: &BTreeMap<usize, T>) -> Option<usize> {
 }
 
 /// Compute the length a data structure will have after applying `updates`.
+///
+/// The length saturates at `usize::MAX` for malformed updates with huge indices. Use
+/// `checked_updated_length` before applying updates.
 pub fn updated_length<U: UpdateMap<T>, T>(prev_len: Length, updates: &U) -> Length {
     updates.max_index().map_or(prev_len, |max_idx| {
-        Length(std::cmp::max(max_idx + 1, prev_len.as_usize()))
+        Length(std::cmp::max(
+            max_idx.saturating_add(1),
+            prev_len.as_usize(),
+        ))
     })
 }
 
+/// Compute the length a data structure will have after applying `updates`.
+///
+/// Errors if the maximum index of `updates` lies beyond the maximum length of a tree.
+pub fn checked_updated_length<U: UpdateMap<T>, T>(
+    prev_len: Length,
+    updates: &U,
+) -> Result<Length, Error> {
+    match updates.max_index() {
+        Some(max_idx) if max_idx >= MAX_TREE_LENGTH => Err(Error::InvalidListUpdate),
+        _ => Ok(updated_length(prev_len, updates)),
+    }
+}
+
+/// Run `a` and `b`, in parallel if the `rayon` feature is enabled.
+#[inline]
+pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    #[cfg(feature = "rayon")]
+    {
+        rayon::join(a, b)
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        (a(), b())
+    }
+}
+
 /// Get the hash of a node at `(depth, prefix)` from an optional HashMap.
 pub fn opt_hash(
     hashes: Option<&BTreeMap<(usize, usize), Hash256>>,
@@ -138,4 +188,25 @@ mod test {
         assert_eq!(compute_level(4, depth, packing_depth), 0);
         assert_eq!(compute_level(8, depth, packing_depth), 3);
     }
+
+    /// Huge indices saturate the length rather than overflowing.
+    #[test]
+    fn updated_length_huge_index() {
+        for index in [MAX_TREE_LENGTH, usize::MAX - 1, usize::MAX] {
+            let updates = BTreeMap::from([(index, 0u64)]);
+            assert_eq!(
+                checked_updated_length(Length(10), &updates),
+                Err(Error::InvalidListUpdate)
+            );
+            assert_eq!(
+                updated_length(Length(10), &updates).as_usize(),
+                index.saturating_add(1)
+            );
+        }
+        let updates = BTreeMap::from([(MAX_TREE_LENGTH - 1, 0u64)]);
+        assert_eq!(
+            checked_updated_length(Length(10), &updates).map(|len| len.as_usize()),
+            Ok(MAX_TREE_LENGTH)
+        );
+    }
 }
@@ -2,12 +2,13 @@ use crate::interface::{ImmList, Interface, MutList};
 use crate::interface_iter::InterfaceIter;
 use crate::iter::Iter;
 use crate::level_iter::LevelIter;
-use crate::tree::RebaseAction;
+use crate::tree::{RebaseAction, RebaseStats, TreeStats};
 use crate::update_map::MaxMap;
-use crate::utils::{arb_arc, Length};
+use crate::utils::{arb_arc, opt_packing_depth, ssz_is_packed_encoding, Length};
 use crate::{Arc, Cow, Error, List, Tree, UpdateMap, Value};
 use arbitrary::Arbitrary;
 use educe::Educe;
+use itertools::process_results;
 use serde::{Deserialize, Serialize};
 use ssz::{Decode, Encode, SszEncoder, TryFromIter, BYTES_PER_LENGTH_OFFSET};
 use std::collections::BTreeMap;
@@ -29,8 +30,7 @@ pub struct Vector<T: Value, N: Unsigned, U: UpdateMap<T> = MaxMap<VecMap<T>>> {
     pub(crate) interface: Interface<T, VectorInner<T, N>, U>,
 }
 
-#[derive(Debug, Educe, Clone, Arbitrary)]
-#[educe(PartialEq(bound(T: Value, N: Unsigned)))]
+#[derive(Debug, Clone, Arbitrary)]
 #[arbitrary(bound = "T: Arbitrary<'arbitrary> + Value, N: Unsigned")]
 pub struct VectorInner<T: Value, N: Unsigned> {
     #[arbitrary(with = arb_arc)]
@@ -57,8 +57,105 @@ impl<T: Value, N: Unsigned, U: UpdateMap<T>> Vector<T, N, U> {
         Self::try_from(List::repeat(elem, N::to_usize())?)
     }
 
+    /// Rotate the vector in place so that the element at `mid` becomes the first element.
+    ///
+    /// Like `slice::rotate_left`, except that `mid` is taken modulo `N`, so rotating by a multiple
+    /// of `N` leaves the vector unchanged. Otherwise the tree is rebuilt from the rotated
+    /// elements, including any pending updates.
+    pub fn rotate_left(&mut self, mid: usize) -> Result<(), Error> {
+        let len = N::to_usize();
+        if len == 0 || mid % len == 0 {
+            return Ok(());
+        }
+        let mid = mid % len;
+        *self = Self::try_from_iter(self.iter_from(mid)?.chain(self.iter().take(mid)).cloned())?;
+        Ok(())
+    }
+
+    /// Rotate the vector in place so that the element at `N - k` becomes the first element.
+    ///
+    /// Like `slice::rotate_right`, except that `k` is taken modulo `N`.
+    pub fn rotate_right(&mut self, k: usize) -> Result<(), Error> {
+        let len = N::to_usize();
+        if len == 0 {
+            return Ok(());
+        }
+        self.rotate_left(len - k % len)
+    }
+
+    /// Set every element of the vector to `value`.
+    ///
+    /// Any pending updates are discarded, and the tree is rebuilt using `repeat`, which shares
+    /// identical subtrees.
+    pub fn fill(&mut self, value: T) -> Result<(), Error> {
+        *self = Self::from_elem(value)?;
+        Ok(())
+    }
+
+    /// Create a vector where the element at each index `i` is `f(i)`.
+    pub fn from_fn(f: impl FnMut(usize) -> T) -> Result<Self, Error> {
+        Self::try_from_iter((0..N::to_usize()).map(f))
+    }
+
+    /// Create a new vector by applying `f` to each element of this vector, in order.
+    pub fn map<R: Value>(&self, f: impl FnMut(&T) -> R) -> Result<Vector<R, N>, Error> {
+        Vector::try_from_iter(self.iter().map(f))
+    }
+
+    /// Create a new vector by applying `f` to each pair of elements at the same index in this
+    /// vector and `other`.
+    pub fn zip_map<R2: Value, U2: UpdateMap<R2>, R: Value>(
+        &self,
+        other: &Vector<R2, N, U2>,
+        mut f: impl FnMut(&T, &R2) -> R,
+    ) -> Result<Vector<R, N>, Error> {
+        Vector::try_from_iter(self.iter().zip(other.iter()).map(|(x, y)| f(x, y)))
+    }
+
     pub fn try_from_iter(iter: impl IntoIterator<Item = T>) -> Result<Self, Error> {
-        Self::try_from(List::try_from_iter(iter)?)
+        let iter = iter.into_iter();
+
+        // Fail fast if the iterator knows its exact length and it is wrong.
+        if let (lower, Some(upper)) = iter.size_hint() {
+            if lower == upper && lower != N::to_usize() {
+                return Err(Error::WrongVectorLength {
+                    len: lower,
+                    expected: N::to_usize(),
+                });
+            }
+        }
+
+        let mut builder = List::<T, N, U>::builder()?;
+
+        for item in iter {
+            builder.push(item)?;
+        }
+
+        let (tree, depth, _) = builder.finish_exact(N::to_usize())?;
+        Ok(Self::from_parts(tree, depth))
+    }
+
+    pub(crate) fn from_parts(tree: Arc<Tree<T>>, depth: usize) -> Self {
+        let packing_depth = opt_packing_depth::<T>().unwrap_or(0);
+        Self {
+            interface: Interface::new(VectorInner {
+                tree,
+                depth,
+                packing_depth,
+                _phantom: PhantomData,
+            }),
+        }
+    }
+
+    /// Copy the vector without sharing any part of its tree with `self`.
+    ///
+    /// Plain `clone` is cheap because the clone shares the tree with the original, and nodes are
+    /// only copied when they are modified. A deep clone copies every node up front, which is
+    /// useful before handing the vector to another thread that will modify most of it.
+    pub fn deep_clone(&self) -> Self {
+        let mut cloned = self.clone();
+        cloned.interface.backing.tree = self.interface.backing.tree.deep_clone();
+        cloned
     }
 
     pub fn to_vec(&self) -> Vec<T> {
@@ -79,15 +176,47 @@ impl<T: Value, N: Unsigned, U: UpdateMap<T>> Vector<T, N, U> {
         Ok(self.interface.iter_from(index))
     }
 
+    /// Iterate mutable references to every element of the vector.
+    ///
+    /// This copies every element into the pending updates up front, so it is only appropriate
+    /// when most elements will be modified. For sparse modifications prefer `get_mut` or
+    /// `get_cow`.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.interface.iter_mut()
+    }
+
     // Wrap trait methods so we present a Vec-like interface without having to import anything.
     pub fn get(&self, index: usize) -> Option<&T> {
         self.interface.get(index)
     }
 
+    /// Get the element at `index`, erroring if `index` is out of bounds.
+    pub fn try_get(&self, index: usize) -> Result<&T, Error> {
+        self.get(index).ok_or_else(|| Error::OutOfBounds {
+            index,
+            len: self.len(),
+        })
+    }
+
     pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
         self.interface.get_mut(index)
     }
 
+    /// Apply `f` to the element at each of `indices`, recording the results as pending updates.
+    ///
+    /// The backing tree is descended once for all indices without pending updates, rather than
+    /// once per index.
+    ///
+    /// Indices are visited in ascending order and at most once each, even if repeated. Errors
+    /// without modifying the vector if any index is out of bounds.
+    pub fn update_each(
+        &mut self,
+        indices: &[usize],
+        f: impl FnMut(usize, &mut T),
+    ) -> Result<(), Error> {
+        self.interface.update_each(indices, f)
+    }
+
     pub fn get_cow(&mut self, index: usize) -> Option<Cow<T>> {
         self.interface.get_cow(index)
     }
@@ -104,9 +233,73 @@ impl<T: Value, N: Unsigned, U: UpdateMap<T>> Vector<T, N, U> {
         self.interface.has_pending_updates()
     }
 
+    /// Iterate the indices with pending updates in index order, without cloning any values.
+    pub fn pending_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.interface.pending_indices()
+    }
+
+    /// Number of indices with pending updates.
+    pub fn pending_len(&self) -> usize {
+        self.interface.pending_len()
+    }
+
+    /// Discard the pending update for `index`, returning the discarded value if present.
+    pub fn discard_pending(&mut self, index: usize) -> Result<Option<T>, Error> {
+        self.interface.discard_pending(index)
+    }
+
+    /// Retain only the pending updates for which `f` returns `true`.
+    pub fn retain_pending(&mut self, f: impl FnMut(usize, &T) -> bool) -> Result<(), Error> {
+        self.interface.retain_pending(f)
+    }
+
+    /// Count the nodes whose hashes will be computed by the next tree hash.
+    ///
+    /// Pending updates are not included, call `apply_updates` first.
+    pub fn dirty_node_count(&self) -> usize {
+        self.interface.backing.tree.dirty_node_count()
+    }
+
+    /// Count the distinct nodes of each kind in the vector's tree.
+    ///
+    /// Pending updates are not included.
+    pub fn structure_stats(&self) -> TreeStats {
+        self.interface.backing.tree.structure_stats()
+    }
+
+    /// Panic if the cached length of the vector doesn't match the length of its tree.
+    ///
+    /// Pending updates are not checked. This is compiled out in release builds.
+    pub fn debug_assert_len_consistent(&self) {
+        debug_assert_eq!(
+            self.interface.backing.len().as_usize(),
+            self.interface.backing.tree.compute_len(),
+            "cached length doesn't match tree"
+        );
+    }
+
+    /// Reserve capacity in the update map for `additional` more updates.
+    ///
+    /// See `UpdateMap::reserve`.
+    pub fn reserve_updates(&mut self, additional: usize) {
+        self.interface.updates.reserve(additional);
+    }
+
     pub fn apply_updates(&mut self) -> Result<(), Error> {
         self.interface.apply_updates()
     }
+
+    /// Apply the pending updates, updating large subtrees in parallel.
+    ///
+    /// This produces the same vector as `apply_updates` and is faster when there are many updates
+    /// scattered across the vector.
+    pub fn apply_updates_par(&mut self) -> Result<(), Error>
+    where
+        T: Send + Sync,
+        U: Sync,
+    {
+        self.interface.apply_updates_par()
+    }
 }
 
 impl<T: Value, N: Unsigned, U: UpdateMap<T>> TryFrom<List<T, N, U>> for Vector<T, N, U> {
@@ -145,21 +338,30 @@ impl<T: Value, N: Unsigned, U: UpdateMap<T>> Vector<T, N, U> {
     }
 
     pub fn rebase_on(&mut self, base: &Self) -> Result<(), Error> {
-        match Tree::rebase_on(
+        self.rebase_on_with_stats(base)?;
+        Ok(())
+    }
+
+    /// Rebase `self` on `base`, returning statistics about how much of the tree was shared.
+    pub fn rebase_on_with_stats(&mut self, base: &Self) -> Result<RebaseStats, Error> {
+        let mut stats = RebaseStats::default();
+        match Tree::rebase_on_with_stats(
             &self.interface.backing.tree,
             &base.interface.backing.tree,
             None,
             self.interface.backing.depth + self.interface.backing.packing_depth,
+            &mut stats,
         )? {
             RebaseAction::EqualReplace(replacement) => {
                 self.interface.backing.tree = replacement.clone();
+                stats.nodes_replaced += 1;
             }
             RebaseAction::NotEqualReplace(replacement) => {
                 self.interface.backing.tree = replacement;
             }
             _ => (),
         }
-        Ok(())
+        Ok(stats)
     }
 }
 
@@ -175,6 +377,15 @@ impl<T: Value, N: Unsigned, U: UpdateMap<T>> From<Vector<T, N, U>> for List<T, N
     }
 }
 
+impl<T: Value, N: Unsigned> PartialEq for VectorInner<T, N> {
+    fn eq(&self, other: &Self) -> bool {
+        // Vectors sharing the same tree are equal without needing to compare their contents.
+        self.depth == other.depth
+            && self.packing_depth == other.packing_depth
+            && (Arc::ptr_eq(&self.tree, &other.tree) || self.tree == other.tree)
+    }
+}
+
 impl<T: Value, N: Unsigned> ImmList<T> for VectorInner<T, N> {
     fn get(&self, index: usize) -> Option<&T> {
         if index < self.len().as_usize() {
@@ -185,6 +396,19 @@ impl<T: Value, N: Unsigned> ImmList<T> for VectorInner<T, N> {
         }
     }
 
+    fn get_many(&self, indices: &[usize]) -> Vec<(usize, &T)> {
+        let end = indices.partition_point(|&index| index < self.len().as_usize());
+        let mut values = Vec::with_capacity(end);
+        self.tree.get_many_recursive(
+            &indices[..end],
+            0,
+            self.depth,
+            self.packing_depth,
+            &mut values,
+        );
+        values
+    }
+
     fn len(&self) -> Length {
         Length(N::to_usize())
     }
@@ -236,6 +460,24 @@ where
                 .with_updated_leaves(&updates, 0, self.depth, hash_updates.as_ref())?;
         Ok(())
     }
+
+    fn update_par<U: UpdateMap<T> + Sync>(&mut self, updates: U) -> Result<(), Error>
+    where
+        T: Send + Sync,
+    {
+        if let Some(max_index) = updates.max_index() {
+            if max_index >= self.len().as_usize() {
+                return Err(Error::InvalidVectorUpdate);
+            }
+        } else {
+            // Nothing to do.
+            return Ok(());
+        }
+        self.tree = self
+            .tree
+            .with_updated_leaves_par(&updates, 0, self.depth, None)?;
+        Ok(())
+    }
 }
 
 impl<T: Default + Value, N: Unsigned> Default for Vector<T, N> {
@@ -250,7 +492,7 @@ impl<T: Default + Value, N: Unsigned> Default for Vector<T, N> {
     }
 }
 
-impl<T: Value + Send + Sync, N: Unsigned> tree_hash::TreeHash for Vector<T, N> {
+impl<T: Value + Send + Sync, N: Unsigned, U: UpdateMap<T>> tree_hash::TreeHash for Vector<T, N, U> {
     fn tree_hash_type() -> tree_hash::TreeHashType {
         tree_hash::TreeHashType::Vector
     }
@@ -270,6 +512,90 @@ impl<T: Value + Send + Sync, N: Unsigned> tree_hash::TreeHash for Vector<T, N> {
     }
 }
 
+impl<T: Value + Send + Sync, N: Unsigned, U: UpdateMap<T>> Vector<T, N, U> {
+    /// Compute the tree hash root of the vector, erroring if there are pending updates.
+    pub fn tree_hash_root_cached(&self) -> Result<Hash256, Error> {
+        if self.has_pending_updates() {
+            return Err(Error::TreeHashPendingUpdates);
+        }
+        Ok(self.interface.backing.tree.tree_hash())
+    }
+
+    /// Check that the tree hash root of the vector is `expected`.
+    ///
+    /// Errors if the roots differ, or if there are pending updates.
+    pub fn verify_root(&self, expected: Hash256) -> Result<(), Error> {
+        let actual = self.tree_hash_root_cached()?;
+        if actual != expected {
+            return Err(Error::RootMismatch { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Get the hash of the node at `index` on `level` of the tree, where level 0 contains the
+    /// leaves and level `depth` contains only the root.
+    ///
+    /// For packed types the leaves are the 32-byte chunks rather than individual elements.
+    /// Return `None` if the position is out of range, or if there are pending updates.
+    pub fn node_hash(&self, index: usize, level: usize) -> Option<Hash256> {
+        if self.has_pending_updates() {
+            return None;
+        }
+        let backing = &self.interface.backing;
+        backing.tree.subtree_hash(index, level, backing.depth)
+    }
+
+    /// Apply pending updates and compute the tree hash root inside the given thread pool.
+    ///
+    /// All parallel hashing runs on `pool`, which allows the caller to bound the parallelism
+    /// used when hashing many vectors concurrently. Computed hashes are cached in the tree as
+    /// usual, so they are shared with clones of this vector and reused by later calls on any
+    /// thread.
+    #[cfg(feature = "rayon")]
+    pub fn tree_hash_root_in(&mut self, pool: &rayon::ThreadPool) -> Result<Hash256, Error>
+    where
+        U: Sync,
+    {
+        self.apply_updates()?;
+        let this = &*self;
+        pool.install(|| this.tree_hash_root_cached())
+    }
+
+    /// Compute the tree hash root of the vector on the current thread, without using rayon.
+    ///
+    /// Errors if there are pending updates.
+    pub fn tree_hash_root_sequential(&self) -> Result<Hash256, Error> {
+        if self.has_pending_updates() {
+            return Err(Error::TreeHashPendingUpdates);
+        }
+        Ok(self.interface.backing.tree.tree_hash_sequential())
+    }
+
+    /// Compute the tree hash root of the vector without caching any newly computed hashes.
+    ///
+    /// See `Tree::tree_hash_readonly`. Errors if there are pending updates.
+    pub fn tree_hash_root_readonly(&self) -> Result<Hash256, Error> {
+        if self.has_pending_updates() {
+            return Err(Error::TreeHashPendingUpdates);
+        }
+        Ok(self.interface.backing.tree.tree_hash_readonly())
+    }
+
+    /// Compute the tree hash root of the vector including any pending updates.
+    ///
+    /// The updates are applied to a copy of the vector, so `self` is left unmodified and the
+    /// hashes of the updated nodes are not cached. Prefer `apply_updates` followed by
+    /// `tree_hash_root` when a mutable reference is available.
+    pub fn tree_hash_root_flushing(&self) -> Result<Hash256, Error> {
+        if !self.has_pending_updates() {
+            return self.tree_hash_root_cached();
+        }
+        let mut flushed = self.clone();
+        flushed.apply_updates()?;
+        flushed.tree_hash_root_cached()
+    }
+}
+
 impl<T, N> TryFromIter<T> for Vector<T, N>
 where
     T: Value,
@@ -285,6 +611,49 @@ where
     }
 }
 
+impl<T: Value, N: Unsigned, U: UpdateMap<T>> TryFrom<Vec<T>> for Vector<T, N, U> {
+    type Error = Error;
+
+    fn try_from(vec: Vec<T>) -> Result<Self, Error> {
+        Self::new(vec)
+    }
+}
+
+impl<T: Value, N: Unsigned, U: UpdateMap<T>> TryFrom<&[T]> for Vector<T, N, U> {
+    type Error = Error;
+
+    fn try_from(slice: &[T]) -> Result<Self, Error> {
+        if slice.len() != N::to_usize() {
+            return Err(Error::WrongVectorLength {
+                len: slice.len(),
+                expected: N::to_usize(),
+            });
+        }
+        Self::try_from_iter(slice.iter().cloned())
+    }
+}
+
+#[cfg(feature = "ssz_types")]
+impl<T: Value, N: Unsigned, U: UpdateMap<T>> From<ssz_types::FixedVector<T, N>>
+    for Vector<T, N, U>
+{
+    fn from(vector: ssz_types::FixedVector<T, N>) -> Self {
+        Self::try_from_iter(Into::<Vec<T>>::into(vector))
+            .expect("FixedVector length always equals N")
+    }
+}
+
+#[cfg(feature = "ssz_types")]
+impl<T: Value, N: Unsigned, U: UpdateMap<T>> TryFrom<Vector<T, N, U>>
+    for ssz_types::FixedVector<T, N>
+{
+    type Error = ssz_types::Error;
+
+    fn try_from(vector: Vector<T, N, U>) -> Result<Self, Self::Error> {
+        Self::new(vector.to_vec())
+    }
+}
+
 impl<'a, T: Value, N: Unsigned, U: UpdateMap<T>> IntoIterator for &'a Vector<T, N, U> {
     type Item = &'a T;
     type IntoIter = InterfaceIter<'a, T, U>;
@@ -295,7 +664,7 @@ impl<'a, T: Value, N: Unsigned, U: UpdateMap<T>> IntoIterator for &'a Vector<T,
 }
 
 // FIXME: duplicated from `ssz::encode::impl_for_vec`
-impl<T: Value, N: Unsigned> Encode for Vector<T, N> {
+impl<T: Value, N: Unsigned, U: UpdateMap<T>> Encode for Vector<T, N, U> {
     fn is_ssz_fixed_len() -> bool {
         <T as Encode>::is_ssz_fixed_len()
     }
@@ -322,8 +691,12 @@ impl<T: Value, N: Unsigned> Encode for Vector<T, N> {
         if <T as Encode>::is_ssz_fixed_len() {
             buf.reserve(<T as Encode>::ssz_fixed_len() * self.len());
 
-            for item in self.iter() {
-                item.ssz_append(buf);
+            if !self.has_pending_updates() && ssz_is_packed_encoding::<T>() {
+                self.interface.backing.tree.ssz_append_packed(buf);
+            } else {
+                for item in self.iter() {
+                    item.ssz_append(buf);
+                }
             }
         } else {
             let mut encoder = SszEncoder::container(buf, self.len() * ssz::BYTES_PER_LENGTH_OFFSET);
@@ -351,11 +724,27 @@ impl<T: Value, N: Unsigned> Decode for Vector<T, N> {
     }
 
     fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, ssz::DecodeError> {
-        let list = List::from_ssz_bytes(bytes).map_err(|e| {
-            ssz::DecodeError::BytesInvalid(format!("Error decoding vector: {:?}", e))
-        })?;
-        Self::try_from(list).map_err(|e| {
-            ssz::DecodeError::BytesInvalid(format!("Wrong number of vector elements: {:?}", e))
-        })
+        if <T as Decode>::is_ssz_fixed_len() {
+            let item_len = <T as Decode>::ssz_fixed_len();
+            if item_len == 0 {
+                return Err(ssz::DecodeError::ZeroLengthItem);
+            }
+            let expected = item_len * N::to_usize();
+            if bytes.len() != expected {
+                return Err(ssz::DecodeError::InvalidByteLength {
+                    len: bytes.len(),
+                    expected,
+                });
+            }
+
+            // Build the vector directly, without going via a `List`.
+            process_results(bytes.chunks(item_len).map(T::from_ssz_bytes), |iter| {
+                Self::try_from_iter(iter).map_err(|e| {
+                    ssz::DecodeError::BytesInvalid(format!("Error building ssz Vector: {:?}", e))
+                })
+            })?
+        } else {
+            ssz::decode_list_of_variable_length_items(bytes, Some(N::to_usize()))
+        }
     }
 }